@@ -0,0 +1,261 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use tauri::command;
+
+use super::posts::{scan_posts, Post};
+use super::settings::load_workspace;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CatalogEntry {
+    pub name: String,
+    /// Number of posts using it
+    pub count: usize,
+}
+
+/// Spellings that likely mean the same tag or category, most used first
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DuplicateGroup {
+    pub names: Vec<String>,
+    /// The most used spelling
+    pub suggested: String,
+}
+
+/// Tags and categories used across all posts
+#[derive(Debug, Serialize)]
+pub struct Catalog {
+    pub tags: Vec<CatalogEntry>,
+    pub categories: Vec<CatalogEntry>,
+    #[serde(rename = "duplicateTags")]
+    pub duplicate_tags: Vec<DuplicateGroup>,
+    #[serde(rename = "duplicateCategories")]
+    pub duplicate_categories: Vec<DuplicateGroup>,
+}
+
+/// Build the tag and category catalog from existing posts
+#[command]
+pub async fn get_catalog() -> Result<Catalog, String> {
+    load_workspace()
+        .and_then(|workspace| scan_posts(&workspace.posts_dir))
+        .map(|posts| build_catalog(&posts))
+        .map_err(|e| e.to_string())
+}
+
+fn build_catalog(posts: &[Post]) -> Catalog {
+    let tags = count_usage(posts.iter().flat_map(|post| post.metadata.tags.iter()));
+    let categories = count_usage(posts.iter().filter_map(|post| post.metadata.category.as_ref()));
+
+    Catalog {
+        duplicate_tags: find_duplicates(&tags),
+        duplicate_categories: find_duplicates(&categories),
+        tags,
+        categories,
+    }
+}
+
+/// Replace tags that differ from an existing tag only by case or punctuation with the
+/// existing spelling
+///
+/// Returns a note for every tag that was changed.
+pub(crate) fn normalize_tags(tags: &mut Vec<String>, posts: &[Post]) -> Vec<String> {
+    let existing = count_usage(posts.iter().flat_map(|post| post.metadata.tags.iter()));
+
+    // Entries are sorted by usage, so the first spelling per key is the most used one
+    let mut canonical: HashMap<String, &str> = HashMap::new();
+    for entry in &existing {
+        canonical
+            .entry(normalize_key(&entry.name))
+            .or_insert(entry.name.as_str());
+    }
+
+    let mut notes = Vec::new();
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags.drain(..) {
+        let tag = match canonical.get(&normalize_key(&tag)) {
+            Some(&spelling) if spelling != tag => {
+                notes.push(format!("Normalized tag '{}' to '{}'", tag, spelling));
+                spelling.to_string()
+            }
+            _ => tag,
+        };
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    *tags = normalized;
+
+    notes
+}
+
+/// Usage counts, most used first and then by name
+fn count_usage<'a>(names: impl Iterator<Item = &'a String>) -> Vec<CatalogEntry> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for name in names {
+        let name = name.trim();
+        if !name.is_empty() {
+            *counts.entry(name).or_default() += 1;
+        }
+    }
+
+    let mut entries: Vec<CatalogEntry> = counts
+        .into_iter()
+        .map(|(name, count)| CatalogEntry {
+            name: name.to_string(),
+            count,
+        })
+        .collect();
+    entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+
+    entries
+}
+
+/// Group entries that differ only by case or punctuation, or that look like the
+/// Hangul and Latin spellings of the same word (e.g. "리액트" and "React")
+fn find_duplicates(entries: &[CatalogEntry]) -> Vec<DuplicateGroup> {
+    // Union-find over entry indices
+    let mut parent: Vec<usize> = (0..entries.len()).collect();
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        parent[i] = root;
+        root
+    }
+
+    let keys: Vec<String> = entries.iter().map(|e| normalize_key(&e.name)).collect();
+    let skeletons: Vec<String> = entries.iter().map(|e| phonetic_skeleton(&e.name)).collect();
+
+    for i in 0..entries.len() {
+        for j in (i + 1)..entries.len() {
+            let same_key = !keys[i].is_empty() && keys[i] == keys[j];
+            let transliterated = is_hangul(&entries[i].name) != is_hangul(&entries[j].name)
+                && word_count(&entries[i].name) == word_count(&entries[j].name)
+                && skeletons[i].len() >= 3
+                && skeletons[i] == skeletons[j];
+            if same_key || transliterated {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                parent[b] = a;
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<&CatalogEntry>> = BTreeMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(entry);
+    }
+
+    groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|group| DuplicateGroup {
+            // Entries are already sorted by usage
+            suggested: group[0].name.clone(),
+            names: group.iter().map(|e| e.name.clone()).collect(),
+        })
+        .collect()
+}
+
+/// Lowercase letters and digits only: "Next.js" and "nextjs" share a key
+fn normalize_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn word_count(name: &str) -> usize {
+    name.split_whitespace().count()
+}
+
+fn is_hangul(name: &str) -> bool {
+    name.chars().any(|c| crate::slug::romanize_syllable(c).is_some())
+}
+
+/// Rough consonant skeleton used to match Korean transliterations of Latin words
+///
+/// Hangul is romanized, the vowel Korean inserts between consonants (`eu`) is dropped,
+/// and consonants Korean doesn't distinguish are merged before vowels are removed,
+/// so "타입스크립트" and "TypeScript" both become `tpskrpt`.
+fn phonetic_skeleton(name: &str) -> String {
+    let romanized: String = name
+        .chars()
+        .map(|c| crate::slug::romanize_syllable(c).unwrap_or_else(|| c.to_lowercase().collect()))
+        .collect::<String>()
+        .replace("eu", "");
+
+    let merged = romanized
+        .replace("ph", "f")
+        .replace("ch", "j")
+        .replace("ck", "k")
+        .replace('x', "ks");
+
+    let mut skeleton = String::new();
+    for c in merged.chars().filter(|c| c.is_ascii_alphanumeric()) {
+        let c = match c {
+            'c' | 'q' | 'g' => 'k',
+            'b' | 'v' | 'f' => 'p',
+            'd' => 't',
+            'l' => 'r',
+            'z' => 'j',
+            'a' | 'e' | 'i' | 'o' | 'u' | 'w' | 'y' | 'h' => continue,
+            c => c,
+        };
+        if !skeleton.ends_with(c) {
+            skeleton.push(c);
+        }
+    }
+
+    skeleton
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::github::PostMetadata;
+
+    fn post(tags: &[&str], category: Option<&str>) -> Post {
+        Post {
+            slug: String::new(),
+            metadata: PostMetadata {
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                category: category.map(str::to_string),
+                ..Default::default()
+            },
+            content: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_build_catalog() {
+        let posts = [
+            post(&["Next.js", "React", "타입스크립트"], Some("Next.js")),
+            post(&["Next.js", "리액트", "Error Handling", "Error Code"], Some("next.js")),
+            post(&["nextjs", "TypeScript", "error-handling", "에러 처리"], Some("Frontend")),
+        ];
+        let catalog = build_catalog(&posts);
+
+        assert_eq!(catalog.tags[0], CatalogEntry { name: "Next.js".to_string(), count: 2 });
+        assert_eq!(catalog.categories.len(), 3);
+
+        let groups: Vec<_> = catalog.duplicate_tags.iter().map(|g| g.names.clone()).collect();
+        assert!(groups.contains(&vec!["Next.js".to_string(), "nextjs".to_string()]));
+        assert!(groups.contains(&vec!["Error Handling".to_string(), "error-handling".to_string()]));
+        assert!(groups.contains(&vec!["React".to_string(), "리액트".to_string()]));
+        assert!(groups.iter().any(|g| g.contains(&"TypeScript".to_string()) && g.contains(&"타입스크립트".to_string())));
+        assert!(!groups.iter().any(|g| g.contains(&"에러 처리".to_string())));
+        assert_eq!(catalog.duplicate_categories[0].suggested, "Next.js");
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let posts = [post(&["Next.js", "Error Handling"], None), post(&["Next.js"], None)];
+        let mut tags = vec!["nextjs".to_string(), "NEXT.JS".to_string(), "error handling".to_string(), "Rust".to_string()];
+
+        let notes = normalize_tags(&mut tags, &posts);
+
+        assert_eq!(tags, ["Next.js", "Error Handling", "Rust"]);
+        assert_eq!(notes.len(), 3);
+    }
+}
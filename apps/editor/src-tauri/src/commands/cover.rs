@@ -0,0 +1,444 @@
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use anyhow::{Context, Result};
+use image::{ImageFormat, Rgb, RgbImage};
+use sha2::{Digest, Sha256};
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use tauri::command;
+
+use super::github::{generate_slug, is_valid_slug, PostMetadata};
+use super::series::find_series;
+use super::settings::{load_workspace, Workspace};
+
+/// Size recommended for `og:image`
+const COVER_WIDTH: u32 = 1200;
+const COVER_HEIGHT: u32 = 630;
+const PADDING: f32 = 80.0;
+
+/// Bump when the layout changes, so covers rendered by an older version aren't reused
+const COVER_VERSION: u32 = 1;
+
+/// Pretendard, the blog's typeface, converted from the blog's subset WOFF2 files. The subset
+/// covers Latin and the 2,780 most common Hangul syllables.
+static BOLD_FONT: &[u8] = include_bytes!("../../fonts/Pretendard-Bold.ttf");
+static REGULAR_FONT: &[u8] = include_bytes!("../../fonts/Pretendard-Regular.ttf");
+
+/// Palette of the blog's `GeneratedThumbnail`, used when the post has no series color
+const THUMBNAIL_PALETTE: [[u8; 3]; 8] = [
+    [0xE7, 0xE5, 0xE4], // Stone
+    [0xFD, 0xE6, 0x8A], // Amber
+    [0xBF, 0xDB, 0xFE], // Blue
+    [0xC4, 0xB5, 0xFD], // Violet
+    [0xBB, 0xF7, 0xD0], // Green
+    [0xFE, 0xCD, 0xD3], // Rose
+    [0xBA, 0xE6, 0xFD], // Sky
+    [0xFE, 0xD7, 0xAA], // Orange
+];
+
+/// What goes on a cover card
+#[derive(Debug)]
+struct CoverCard {
+    title: String,
+    description: String,
+    category: Option<String>,
+    /// Title of the post's series
+    series: Option<String>,
+    background: [u8; 3],
+}
+
+/// A cover card and the file it is rendered to
+#[derive(Debug)]
+pub(crate) struct PlannedCover {
+    card: CoverCard,
+    pub path: PathBuf,
+    /// Public path, e.g. `/images/posts/<slug>-cover_<hash>.png`
+    pub url: String,
+}
+
+/// Render an Open Graph card for the post and return its public path
+///
+/// The card shows the title, description, category and series title on the series color.
+/// `existing_slug` is set when editing a published post, as for `submit_post`.
+#[command]
+pub async fn generate_cover(
+    metadata: PostMetadata,
+    existing_slug: Option<String>,
+) -> Result<String, String> {
+    let slug = match existing_slug
+        .or_else(|| metadata.slug.clone())
+        .map(|slug| slug.trim().to_string())
+        .filter(|slug| !slug.is_empty())
+    {
+        Some(slug) if !is_valid_slug(&slug) => return Err(format!("Invalid slug '{}'", slug)),
+        Some(slug) => slug,
+        None => generate_slug(&metadata.title),
+    };
+
+    load_workspace()
+        .and_then(|workspace| generate_cover_internal(&workspace, &metadata, &slug))
+        .map_err(|e| e.to_string())
+}
+
+/// Render the cover card into the images directory as `<slug>-cover_<hash>.png` and return
+/// its public path
+///
+/// PNG rather than WebP, since not every link preview crawler reads WebP. A card with the
+/// same text and color is reused.
+pub(crate) fn generate_cover_internal(
+    workspace: &Workspace,
+    metadata: &PostMetadata,
+    slug: &str,
+) -> Result<String> {
+    let planned = plan_cover(workspace, metadata, slug)?;
+
+    if planned.path.exists() {
+        println!("Reusing cover image: {:?}", planned.path);
+    } else {
+        let png = render_cover(&planned.card)?;
+        std::fs::create_dir_all(&workspace.images_dir)
+            .context("Failed to create images directory")?;
+        let mut temp = tempfile::NamedTempFile::new_in(&workspace.images_dir)?;
+        temp.write_all(&png)?;
+        temp.persist(&planned.path).map_err(|e| e.error)?;
+        println!("Generated cover image: {:?}", planned.path);
+    }
+
+    Ok(planned.url)
+}
+
+/// Work out the card for the post and where `generate_cover_internal` puts it, without
+/// rendering anything
+pub(crate) fn plan_cover(workspace: &Workspace, metadata: &PostMetadata, slug: &str) -> Result<PlannedCover> {
+    if metadata.title.trim().is_empty() {
+        anyhow::bail!("A title is required to generate a cover");
+    }
+
+    let series = match metadata.series.as_deref() {
+        Some(id) => find_series(&workspace.series_file, id)?,
+        None => None,
+    };
+    let background = series
+        .as_ref()
+        .and_then(|s| s.color.as_deref())
+        .and_then(parse_hex_color)
+        .unwrap_or_else(|| thumbnail_color(&metadata.title));
+    let card = CoverCard {
+        title: metadata.title.trim().to_string(),
+        description: metadata.description.trim().to_string(),
+        category: metadata
+            .category
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string),
+        series: series.map(|s| s.title),
+        background,
+    };
+
+    let slug = if slug.is_empty() { "post" } else { slug };
+    let file_name = format!("{}-cover_{}.png", slug, card_key(&card));
+
+    Ok(PlannedCover {
+        card,
+        path: workspace.images_dir.join(&file_name),
+        url: workspace.image_url(&file_name),
+    })
+}
+
+/// Hash of everything drawn on the card
+fn card_key(card: &CoverCard) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(COVER_VERSION.to_le_bytes());
+    let texts = [
+        Some(&card.title),
+        Some(&card.description),
+        card.category.as_ref(),
+        card.series.as_ref(),
+    ];
+    for text in texts {
+        hasher.update(text.map_or("", String::as_str).as_bytes());
+        hasher.update([0]);
+    }
+    hasher.update(card.background);
+
+    hasher.finalize()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn render_cover(card: &CoverCard) -> Result<Vec<u8>> {
+    let bold = FontRef::try_from_slice(BOLD_FONT).context("Failed to load the bold font")?;
+    let regular = FontRef::try_from_slice(REGULAR_FONT).context("Failed to load the regular font")?;
+
+    let mut canvas = RgbImage::from_pixel(COVER_WIDTH, COVER_HEIGHT, Rgb(card.background));
+    let text_color = text_color(card.background);
+    let max_width = COVER_WIDTH as f32 - PADDING * 2.0;
+
+    // Category at the top, series at the bottom, title and description centered between
+    let top = PADDING;
+    let bottom = COVER_HEIGHT as f32 - PADDING;
+    if let Some(category) = &card.category {
+        let line = wrap_text(&regular, 32.0, category, max_width, 1);
+        draw_lines(&mut canvas, &regular, 32.0, 1.2, &line, top, text_color, 0.75);
+    }
+    if let Some(series) = &card.series {
+        let line = wrap_text(&regular, 28.0, series, max_width, 1);
+        draw_lines(&mut canvas, &regular, 28.0, 1.2, &line, bottom - 28.0 * 1.2, text_color, 0.75);
+    }
+
+    let title = wrap_text(&bold, 60.0, &card.title, max_width, 3);
+    let description = wrap_text(&regular, 30.0, &card.description, max_width, 2);
+    let title_height = title.len() as f32 * 60.0 * 1.3;
+    let description_height = description.len() as f32 * 30.0 * 1.5;
+    let gap = if description.is_empty() { 0.0 } else { 24.0 };
+    let block_top = (COVER_HEIGHT as f32 - title_height - gap - description_height) / 2.0;
+
+    draw_lines(&mut canvas, &bold, 60.0, 1.3, &title, block_top, text_color, 1.0);
+    draw_lines(
+        &mut canvas,
+        &regular,
+        30.0,
+        1.5,
+        &description,
+        block_top + title_height + gap,
+        text_color,
+        0.75,
+    );
+
+    let mut png = Vec::new();
+    canvas
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .context("Failed to encode the cover image")?;
+    Ok(png)
+}
+
+/// Break `text` into at most `max_lines` lines no wider than `max_width`, ending the last
+/// line with an ellipsis when the text doesn't fit
+///
+/// Lines break between words; a word wider than a line breaks between characters.
+fn wrap_text(font: &FontRef, size: f32, text: &str, max_width: f32, max_lines: usize) -> Vec<String> {
+    let width = |s: &str| line_width(font, size, s);
+
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", current, word)
+        };
+        if width(&candidate) <= max_width {
+            current = candidate;
+            continue;
+        }
+
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        for c in word.chars() {
+            current.push(c);
+            if width(&current) > max_width && current.chars().count() > 1 {
+                current.pop();
+                lines.push(std::mem::replace(&mut current, c.to_string()));
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            while !last.is_empty() && width(&format!("{}…", last)) > max_width {
+                last.pop();
+            }
+            *last = format!("{}…", last.trim_end());
+        }
+    }
+
+    lines
+}
+
+fn line_width(font: &FontRef, size: f32, text: &str) -> f32 {
+    let font = font.as_scaled(em_scale(font, size));
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            width += font.kern(previous, id);
+        }
+        width += font.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// Scale at which the em square is `size` pixels, like CSS `font-size`
+///
+/// `PxScale` is the height from descender to ascender, which is taller than the em.
+fn em_scale(font: &FontRef, size: f32) -> PxScale {
+    let units_per_em = font.units_per_em().unwrap_or(1000.0);
+    PxScale::from(size * font.height_unscaled() / units_per_em)
+}
+
+/// Draw lines of text from `top`, each `size * line_height` pixels tall
+#[allow(clippy::too_many_arguments)]
+fn draw_lines(
+    canvas: &mut RgbImage,
+    font: &FontRef,
+    size: f32,
+    line_height: f32,
+    lines: &[String],
+    top: f32,
+    color: [u8; 3],
+    opacity: f32,
+) {
+    let scale = em_scale(font, size);
+    let scaled = font.as_scaled(scale);
+    // Center the glyphs' ascent and descent in the line box
+    let leading = size * line_height - (scaled.ascent() - scaled.descent());
+
+    for (i, line) in lines.iter().enumerate() {
+        let baseline = top + size * line_height * i as f32 + leading / 2.0 + scaled.ascent();
+        let mut x = PADDING;
+        let mut previous = None;
+
+        for c in line.chars() {
+            let id = scaled.glyph_id(c);
+            if let Some(previous) = previous {
+                x += scaled.kern(previous, id);
+            }
+            let glyph = id.with_scale_and_position(scale, point(x, baseline));
+            x += scaled.h_advance(id);
+            previous = Some(id);
+
+            let Some(outlined) = font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i32 + gx as i32;
+                let py = bounds.min.y as i32 + gy as i32;
+                if px < 0 || py < 0 || px >= COVER_WIDTH as i32 || py >= COVER_HEIGHT as i32 {
+                    return;
+                }
+                let alpha = coverage.clamp(0.0, 1.0) * opacity;
+                let pixel = canvas.get_pixel_mut(px as u32, py as u32);
+                for (channel, &target) in pixel.0.iter_mut().zip(&color) {
+                    *channel = (*channel as f32 * (1.0 - alpha) + target as f32 * alpha).round() as u8;
+                }
+            });
+        }
+    }
+}
+
+/// `#RRGGBB`, as stored in `series.json`
+fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Same color the blog's `generateThumbnailColor` picks for the title, so the cover matches
+/// the thumbnail the post had without one
+fn thumbnail_color(title: &str) -> [u8; 3] {
+    // JavaScript's `hash = c + ((hash << 5) - hash)` over UTF-16 code units, where `<<`
+    // truncates to 32 bits but the sum doesn't
+    let mut hash: i64 = 0;
+    for unit in title.encode_utf16() {
+        hash = unit as i64 + (((hash as i32).wrapping_shl(5)) as i64 - hash);
+    }
+    THUMBNAIL_PALETTE[(hash.unsigned_abs() % THUMBNAIL_PALETTE.len() as u64) as usize]
+}
+
+/// Black on light backgrounds and white on dark ones, like the blog's `getTextColor`
+fn text_color(background: [u8; 3]) -> [u8; 3] {
+    let [r, g, b] = background.map(u32::from);
+    let brightness = (r * 299 + g * 587 + b * 114) / 1000;
+    if brightness > 128 {
+        [0, 0, 0]
+    } else {
+        [255, 255, 255]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::settings::test_workspace;
+
+    #[test]
+    fn test_thumbnail_color_matches_blog() {
+        // Values from the blog's generateThumbnailColor
+        assert_eq!(thumbnail_color("Hello"), THUMBNAIL_PALETTE[2]);
+        assert_eq!(thumbnail_color("리액트 성능 최적화"), THUMBNAIL_PALETTE[0]);
+        assert_eq!(
+            thumbnail_color("A very long English title that overflows int32 many times over"),
+            THUMBNAIL_PALETTE[6]
+        );
+        assert_eq!(text_color([0x8B, 0x5C, 0xF6]), [255, 255, 255]);
+        assert_eq!(text_color([0xF5, 0x9E, 0x0B]), [0, 0, 0]);
+        assert_eq!(parse_hex_color("#8B5CF6"), Some([0x8B, 0x5C, 0xF6]));
+        assert_eq!(parse_hex_color("8B5CF6"), None);
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        let font = FontRef::try_from_slice(BOLD_FONT).unwrap();
+
+        assert_eq!(wrap_text(&font, 60.0, "짧은 제목", 1040.0, 3), ["짧은 제목"]);
+
+        let title = "한국어 제목이 아주 길어서 여러 줄로 나뉘어야 하는 경우에도 단어 단위로 줄을 바꾸고 넘치면 말줄임표를 붙인다. 그래도 남는 내용은 잘린다";
+        let lines = wrap_text(&font, 60.0, title, 1040.0, 3);
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line_width(&font, 60.0, line) <= 1040.0));
+        assert!(lines[2].ends_with('…'));
+        // Breaks fall between words
+        assert!(title.starts_with(&format!("{} {}", lines[0], lines[1])));
+
+        let lines = wrap_text(&font, 60.0, &"가".repeat(40), 1040.0, 3);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line_width(&font, 60.0, line) <= 1040.0));
+    }
+
+    #[test]
+    fn test_generate_cover() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = test_workspace(&dir);
+        std::fs::write(&workspace.series_file, r##"{"web-performance":{"title":"웹 성능","color":"#1E3A8A"}}"##).unwrap();
+        let metadata = PostMetadata {
+            title: "Core Web Vitals 개선기".to_string(),
+            description: "LCP와 INP를 줄인 과정".to_string(),
+            category: Some("Performance".to_string()),
+            series: Some("web-performance".to_string()),
+            ..Default::default()
+        };
+
+        let url = generate_cover_internal(&workspace, &metadata, "cwv").unwrap();
+        assert!(url.starts_with("/images/posts/cwv-cover_") && url.ends_with(".png"));
+        let path = workspace.image_file(&url).unwrap();
+        let img = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(img.dimensions(), (COVER_WIDTH, COVER_HEIGHT));
+        // Series color in the corner, white text somewhere on it
+        assert_eq!(img.get_pixel(0, 0).0, [0x1E, 0x3A, 0x8A]);
+        assert!(img.pixels().any(|p| p.0 == [255, 255, 255]));
+
+        // Same card, same file; a changed title renders a new one
+        assert_eq!(generate_cover_internal(&workspace, &metadata, "cwv").unwrap(), url);
+        let renamed = PostMetadata {
+            title: "Core Web Vitals 개선기 2".to_string(),
+            ..metadata.clone()
+        };
+        assert_ne!(generate_cover_internal(&workspace, &renamed, "cwv").unwrap(), url);
+        assert_eq!(std::fs::read_dir(&workspace.images_dir).unwrap().count(), 2);
+
+        let untitled = PostMetadata::default();
+        assert!(generate_cover_internal(&workspace, &untitled, "cwv").is_err());
+    }
+}
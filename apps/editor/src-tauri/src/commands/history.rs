@@ -0,0 +1,319 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::command;
+
+use super::github::{
+    git_output, is_valid_slug, publish_post_changes, PublishMode, SubmitError, SubmitPostResult,
+};
+use super::posts::{parse_mdx, Post};
+use super::settings::{load_workspace, Workspace};
+
+/// A commit that changed a post
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PostRevision {
+    /// Full commit hash
+    pub commit: String,
+    /// Author date, RFC 3339
+    pub date: String,
+    pub author: String,
+    /// First line of the commit message
+    pub message: String,
+    /// Lines of the post added and removed by the commit
+    pub additions: usize,
+    pub deletions: usize,
+    /// Whether the commit deleted the post
+    pub deleted: bool,
+}
+
+/// List the commits that changed a post, newest first
+#[command]
+pub async fn list_post_revisions(slug: String) -> Result<Vec<PostRevision>, String> {
+    load_workspace()
+        .and_then(|workspace| list_post_revisions_internal(&workspace, &slug))
+        .map_err(|e| e.to_string())
+}
+
+/// Load a post as it was at `commit`
+#[command]
+pub async fn load_post_revision(slug: String, commit: String) -> Result<Post, String> {
+    load_workspace()
+        .and_then(|workspace| {
+            let source = read_revision(&workspace, &slug, &commit)?;
+            let (mut metadata, content) = parse_mdx(&source)
+                .with_context(|| format!("Failed to parse {}.mdx at {}", slug, commit))?;
+            metadata.slug = Some(slug.clone());
+            Ok(Post {
+                slug,
+                metadata,
+                content,
+            })
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Write a post back as it was at `commit` and publish that as a new commit
+///
+/// The change is committed the same way `submit_post` publishes a post, so the history
+/// keeps every revision. A deleted post can be restored from a commit before its deletion;
+/// images it referenced that are gone are restored from the same commit.
+#[command]
+pub async fn restore_post_revision(
+    slug: String,
+    commit: String,
+    publish_mode: Option<PublishMode>,
+) -> Result<SubmitPostResult, SubmitError> {
+    let workspace = load_workspace().map_err(SubmitError::from)?;
+    restore_post_revision_internal(&workspace, &slug, &commit, publish_mode.unwrap_or_default())
+        .await
+        .map_err(SubmitError::from)
+}
+
+fn list_post_revisions_internal(workspace: &Workspace, slug: &str) -> Result<Vec<PostRevision>> {
+    let path = post_pathspec(workspace, slug)?;
+
+    // Records start with \x1e and header fields are separated by \x1f; `--raw` tells
+    // deletions apart and `--numstat` counts the lines
+    let log = git_output(
+        &workspace.root,
+        &[
+            "log",
+            "--no-renames",
+            "--format=%x1e%H%x1f%aI%x1f%an%x1f%s",
+            "--raw",
+            "--numstat",
+            "--",
+            &path,
+        ],
+    )?;
+
+    let mut revisions = Vec::new();
+    for record in log.split('\u{1e}').filter(|record| !record.trim().is_empty()) {
+        let mut lines = record.lines();
+        let header: Vec<&str> = lines.next().unwrap_or_default().split('\u{1f}').collect();
+        let [commit, date, author, message] = header[..] else {
+            anyhow::bail!("Unexpected git log output: {:?}", record);
+        };
+
+        let mut revision = PostRevision {
+            commit: commit.to_string(),
+            date: date.to_string(),
+            author: author.to_string(),
+            message: message.to_string(),
+            additions: 0,
+            deletions: 0,
+            deleted: false,
+        };
+        for line in lines.filter(|line| !line.is_empty()) {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if line.starts_with(':') {
+                // `:100644 000000 <blob> <blob> D\tposts/a.mdx`
+                revision.deleted |= fields[0].ends_with('D');
+            } else if let [additions, deletions, _] = fields[..] {
+                // Binary files show `-`
+                revision.additions += additions.parse::<usize>().unwrap_or(0);
+                revision.deletions += deletions.parse::<usize>().unwrap_or(0);
+            }
+        }
+        revisions.push(revision);
+    }
+
+    Ok(revisions)
+}
+
+/// The post's MDX source as committed in `commit`
+fn read_revision(workspace: &Workspace, slug: &str, commit: &str) -> Result<String> {
+    let path = post_pathspec(workspace, slug)?;
+    // Only a hash is accepted, so the argument can't be read as an option or a range
+    let valid_commit = (4..=40).contains(&commit.len()) && commit.chars().all(|c| c.is_ascii_hexdigit());
+    if !valid_commit {
+        anyhow::bail!("Invalid commit: {}", commit);
+    }
+
+    git_output(&workspace.root, &["show", &format!("{}:{}", commit, path)])
+        .with_context(|| format!("{}.mdx doesn't exist at {}", slug, commit))
+}
+
+async fn restore_post_revision_internal(
+    workspace: &Workspace,
+    slug: &str,
+    commit: &str,
+    publish_mode: PublishMode,
+) -> Result<SubmitPostResult> {
+    let source = read_revision(workspace, slug, commit)?;
+    let (mut metadata, _) = parse_mdx(&source)
+        .with_context(|| format!("Failed to parse {}.mdx at {}", slug, commit))?;
+    metadata.slug = Some(slug.to_string());
+
+    let mdx_path = workspace.posts_dir.join(format!("{}.mdx", slug));
+    if std::fs::read_to_string(&mdx_path).is_ok_and(|current| current == source) {
+        anyhow::bail!("The post already matches {}", short_hash(commit));
+    }
+    let commit_message = format!("feat(blog): restore post - {} ({})", metadata.title, short_hash(commit));
+
+    let (images, mut warnings) = missing_revision_images(workspace, &source, commit);
+
+    let mut paths = vec![mdx_path.clone()];
+    paths.extend(images.iter().map(|(path, _)| path.clone()));
+
+    let (commit_warnings, pull_request_url) = publish_post_changes(
+        workspace,
+        &paths,
+        &[],
+        &commit_message,
+        slug,
+        &metadata,
+        publish_mode,
+        || {
+            for (path, data) in &images {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, data).with_context(|| format!("Failed to write {:?}", path))?;
+            }
+            std::fs::write(&mdx_path, &source).context("Failed to write MDX file")
+        },
+    )
+    .await?;
+    warnings.extend(commit_warnings);
+
+    println!("Restored {} to {}", slug, commit);
+
+    Ok(SubmitPostResult {
+        slug: slug.to_string(),
+        warnings,
+        pull_request_url,
+    })
+}
+
+/// Images the post's source at `commit` references that no longer exist, read from the
+/// same commit, and warnings for those the commit doesn't have either
+///
+/// Images are usually deleted along with the post, so restoring it brings them back.
+fn missing_revision_images(
+    workspace: &Workspace,
+    source: &str,
+    commit: &str,
+) -> (Vec<(PathBuf, Vec<u8>)>, Vec<String>) {
+    let mut images: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    let mut warnings = Vec::new();
+    for url in crate::markdown::find_urls_with_prefix(source, &workspace.public_url_prefix) {
+        let Some(path) = workspace.image_file(&url) else {
+            continue;
+        };
+        if path.exists() || images.iter().any(|(image, _)| image == &path) {
+            continue;
+        }
+        match read_revision_file(workspace, commit, &path) {
+            Ok(data) => images.push((path, data)),
+            Err(_) => warnings.push(format!("{} doesn't exist at {}", url, short_hash(commit))),
+        }
+    }
+    (images, warnings)
+}
+
+/// A file's bytes as committed in `commit`; `path` is absolute inside the repository
+fn read_revision_file(workspace: &Workspace, commit: &str, path: &Path) -> Result<Vec<u8>> {
+    let relative = path
+        .strip_prefix(&workspace.root)
+        .context("The file is outside the blog repository")?
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    // Images are binary, so the output isn't read as text like `git_output` does
+    let output = Command::new("git")
+        .current_dir(&workspace.root)
+        .args(["show", &format!("{}:{}", commit, relative)])
+        .output()
+        .context("Failed to execute git show")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git show failed: {}", stderr);
+    }
+
+    Ok(output.stdout)
+}
+
+/// Path of the post's `.mdx` file relative to the repository, as git spells it
+fn post_pathspec(workspace: &Workspace, slug: &str) -> Result<String> {
+    if !is_valid_slug(slug) {
+        anyhow::bail!("Invalid post slug: {}", slug);
+    }
+
+    let path = workspace.posts_dir.join(format!("{}.mdx", slug));
+    let relative = path
+        .strip_prefix(&workspace.root)
+        .context("The posts directory is outside the blog repository")?;
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn short_hash(commit: &str) -> &str {
+    &commit[..commit.len().min(7)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::settings::test_workspace;
+    use std::process::Command;
+
+    #[test]
+    fn test_post_revisions() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = test_workspace(&dir);
+        let git = |args: &[&str]| {
+            let output = Command::new("git").current_dir(&workspace.root).args(args).output().unwrap();
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        let commit = |message: &str| {
+            git(&["add", "-A"]);
+            git(&["-c", "user.name=Test", "-c", "user.email=test@example.com", "commit", "-q", "-m", message]);
+            git(&["rev-parse", "HEAD"])
+        };
+
+        git(&["init", "-q"]);
+        let post = workspace.posts_dir.join("hello.mdx");
+        std::fs::write(&post, "---\ntitle: Hello\ndescription: d\ncreatedAt: 2024-01-01\n---\n\nFirst\n").unwrap();
+        std::fs::write(workspace.images_dir.join("a.png"), [0x89, b'P', b'N', b'G', 0xff]).unwrap();
+        let first = commit("feat(blog): add post - Hello");
+        std::fs::write(workspace.posts_dir.join("other.mdx"), "other").unwrap();
+        commit("unrelated");
+        std::fs::write(&post, "---\ntitle: Hello again\ndescription: d\ncreatedAt: 2024-01-01\n---\n\nSecond\nThird\n").unwrap();
+        let second = commit("feat(blog): update post - Hello again");
+        std::fs::remove_file(&post).unwrap();
+        let third = commit("feat(blog): delete post - Hello again");
+
+        let revisions = list_post_revisions_internal(&workspace, "hello").unwrap();
+        let summary: Vec<_> = revisions
+            .iter()
+            .map(|r| (r.commit.as_str(), r.message.as_str(), r.additions, r.deletions, r.deleted))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (third.as_str(), "feat(blog): delete post - Hello again", 0, 8, true),
+                (second.as_str(), "feat(blog): update post - Hello again", 3, 2, false),
+                (first.as_str(), "feat(blog): add post - Hello", 7, 0, false),
+            ]
+        );
+        assert_eq!(revisions[0].author, "Test");
+        assert!(revisions[0].date.starts_with("20"));
+
+        let source = read_revision(&workspace, "hello", &first[..7]).unwrap();
+        assert_eq!(parse_mdx(&source).unwrap().1, "First\n");
+
+        // The image was deleted with the post and comes back byte for byte
+        std::fs::remove_dir_all(&workspace.images_dir).unwrap();
+        let source = "![a](/images/posts/a.png) ![b](/images/posts/b.png) ![a](/images/posts/a.png)";
+        let (images, warnings) = missing_revision_images(&workspace, source, &first);
+        assert_eq!(images, [(workspace.images_dir.join("a.png"), vec![0x89, b'P', b'N', b'G', 0xff])]);
+        assert_eq!(warnings, [format!("/images/posts/b.png doesn't exist at {}", &first[..7])]);
+        assert!(read_revision(&workspace, "hello", &third).is_err());
+        assert!(read_revision(&workspace, "hello", "--output=x").is_err());
+        assert!(list_post_revisions_internal(&workspace, "../hello").is_err());
+        assert!(list_post_revisions_internal(&workspace, "missing").unwrap().is_empty());
+    }
+}
@@ -1,129 +1,780 @@
-use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use image::imageops::FilterType;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, DynamicImage, Frame, ImageDecoder, ImageFormat, ImageReader};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tauri::command;
 
+use super::settings::{load_workspace, ImageOptions, Workspace};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MediaKind {
+    /// Still WebP with responsive variants
+    Image,
+    /// Animated WebP
+    Animated,
+    /// MP4, for videos and animations too large as WebP
+    Video,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProcessImageResult {
+    /// Which element to insert for `path`
+    pub kind: MediaKind,
     /// Path to use in markdown (relative to public folder)
     pub path: String,
     /// Absolute local path of the processed image
+    #[serde(rename = "localPath")]
     pub local_path: String,
+    /// Intrinsic size of the image at `path`
+    pub width: u32,
+    pub height: u32,
+    /// Smaller copies of a still image, narrowest first
+    pub variants: Vec<ImageVariant>,
+    /// `srcSet` covering the variants and the full-size image
+    #[serde(rename = "srcSet")]
+    pub src_set: String,
+    /// Tiny WebP as a `data:` URL, shown blurred while the image loads
+    #[serde(rename = "blurDataUrl")]
+    pub blur_data_url: String,
+    /// First frame of a video, for the `poster` attribute
+    pub poster: Option<String>,
+    /// Metadata of the upload that the processed image doesn't carry
+    pub stripped: StrippedMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImageVariant {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
 }
 
-/// Process an image: compress and convert to WebP using ffmpeg
+/// Metadata found in an upload; processed images keep none of it
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StrippedMetadata {
+    /// EXIF orientation (2 to 8) that was applied to the pixels instead
+    pub orientation: Option<u32>,
+    /// Whether the EXIF or XMP data held GPS coordinates
+    #[serde(rename = "hasLocation")]
+    pub has_location: bool,
+    /// Names of the EXIF fields, e.g. `Model` or `GPSLatitude`; for videos, the container
+    /// tags, e.g. `creation_time` or `location`
+    #[serde(rename = "exifFields")]
+    pub exif_fields: Vec<String>,
+    pub xmp: bool,
+    #[serde(rename = "iccProfile")]
+    pub icc_profile: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImageError {
+    #[error("Could not decode image: {0}")]
+    Decode(String),
+    #[error("Unsupported image format; install ffmpeg to convert it")]
+    UnsupportedFormat,
+    #[error("ffmpeg failed: {0}")]
+    Ffmpeg(String),
+    #[error("Could not encode WebP: {0}")]
+    Encode(String),
+    #[error("Converted file is {size} bytes, over the {limit} byte limit; shorten or shrink it first")]
+    TooLarge { size: u64, limit: u64 },
+    #[error("Failed to write image: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// Process an image: resize and convert to WebP, or to animated WebP or MP4 for animations
+/// and videos
 #[command]
 pub async fn process_image(
     file_name: String,
     data: Vec<u8>,
 ) -> Result<ProcessImageResult, String> {
-    process_image_internal(file_name, data)
+    let workspace = load_workspace().map_err(|e| e.to_string())?;
+    process_image_internal(&workspace, file_name, data)
         .await
         .map_err(|e| e.to_string())
 }
 
-async fn process_image_internal(
+/// Report the metadata `process_image` would strip from an image, without processing it
+#[command]
+pub async fn inspect_image_metadata(data: Vec<u8>) -> Result<StrippedMetadata, String> {
+    inspect_metadata(&data).map_err(|e| e.to_string())
+}
+
+pub(crate) async fn process_image_internal(
+    workspace: &Workspace,
+    file_name: String,
+    data: Vec<u8>,
+) -> Result<ProcessImageResult, ImageError> {
+    let images_dir = workspace.images_dir.clone();
+    let options = workspace.image.clone();
+
+    // Decoding and encoding are CPU bound; keep them off the async runtime
+    let (saved, stripped) = tokio::task::spawn_blocking(move || {
+        process_into(&images_dir, &file_name, &data, &options)
+    })
+    .await
+    .map_err(anyhow::Error::from)??;
+
+    let url = |path: &Path| workspace.image_url(&file_name_of(path));
+
+    let variants: Vec<ImageVariant> = saved
+        .variants
+        .iter()
+        .map(|(path, width, height)| ImageVariant {
+            path: url(path),
+            width: *width,
+            height: *height,
+        })
+        .collect();
+    let path = url(&saved.path);
+
+    let src_set = variants
+        .iter()
+        .map(|v| (v.path.as_str(), v.width))
+        .chain(std::iter::once((path.as_str(), saved.width)))
+        .map(|(path, width)| format!("{} {}w", path, width))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Ok(ProcessImageResult {
+        kind: saved.kind,
+        local_path: saved.path.to_string_lossy().to_string(),
+        path,
+        width: saved.width,
+        height: saved.height,
+        variants,
+        src_set,
+        blur_data_url: saved.blur_data_url,
+        poster: saved.poster.as_deref().map(url),
+        stripped,
+    })
+}
+
+/// Work out what `process_image_internal` would do with an upload without touching the
+/// images directory
+///
+/// Returns the public path the upload would get and the files it would add to the images
+/// directory, which is empty when an identical upload was processed before. A new upload is
+/// processed in a scratch directory, since whether an animation ends up as WebP or MP4
+/// depends on the encoded size.
+pub(crate) async fn plan_image_internal(
+    workspace: &Workspace,
     file_name: String,
     data: Vec<u8>,
-) -> Result<ProcessImageResult> {
-    // Get the blog's public images directory
-    let blog_path = get_blog_images_path()?;
-    
+) -> Result<(String, Vec<PathBuf>), ImageError> {
+    let images_dir = workspace.images_dir.clone();
+    let options = workspace.image.clone();
+
+    let (name, added) = tokio::task::spawn_blocking(move || {
+        let key = content_key(&data, &options);
+        if let Some(existing) = find_processed(&images_dir, &key)? {
+            return Ok((file_name_of(&existing), Vec::new()));
+        }
+
+        let scratch = tempfile::tempdir()?;
+        let (saved, _) = process_into(scratch.path(), &file_name, &data, &options)?;
+        let mut added: Vec<PathBuf> = std::fs::read_dir(scratch.path())?
+            .map(|entry| entry.map(|entry| images_dir.join(entry.file_name())))
+            .collect::<Result<_, _>>()?;
+        added.sort();
+        Ok::<_, ImageError>((file_name_of(&saved.path), added))
+    })
+    .await
+    .map_err(anyhow::Error::from)??;
+
+    Ok((workspace.image_url(&name), added))
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// An upload encoded at full size, plus variant widths, a poster and a blur placeholder
+struct EncodedImage {
+    kind: MediaKind,
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+    /// `(width, height, webp)`, narrowest first
+    variants: Vec<(u32, u32, Vec<u8>)>,
+    /// WebP of the first frame of a video
+    poster: Option<Vec<u8>>,
+    placeholder: Vec<u8>,
+    stripped: StrippedMetadata,
+}
+
+impl EncodedImage {
+    fn extension(&self) -> &'static str {
+        match self.kind {
+            MediaKind::Image | MediaKind::Animated => "webp",
+            MediaKind::Video => "mp4",
+        }
+    }
+}
+
+/// Files written for an [`EncodedImage`]
+struct SavedImage {
+    kind: MediaKind,
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    variants: Vec<(PathBuf, u32, u32)>,
+    poster: Option<PathBuf>,
+    blur_data_url: String,
+}
+
+/// Width of the blur placeholder; it is scaled up and blurred by the browser
+const PLACEHOLDER_WIDTH: u32 = 16;
+
+/// Bump when the output for the same source and settings changes, so images processed
+/// by an older pipeline aren't reused
+const PIPELINE_VERSION: u32 = 2;
+
+/// Process an image into `images_dir`, reusing an earlier result for the same source bytes
+/// and settings
+fn process_into(
+    images_dir: &Path,
+    file_name: &str,
+    data: &[u8],
+    options: &ImageOptions,
+) -> Result<(SavedImage, StrippedMetadata), ImageError> {
+    let key = content_key(data, options);
+
+    if let Some(existing) = find_processed(images_dir, &key)? {
+        println!("Reusing processed image: {:?}", existing);
+        return Ok((load_saved(&existing, options)?, inspect_metadata(data)?));
+    }
+
+    let mut encoded = match detect_kind(data) {
+        MediaKind::Image => encode_webp(data, options)?,
+        MediaKind::Animated => encode_animation(data, options)?,
+        MediaKind::Video => encode_video(data, options)?,
+    };
+    let stripped = std::mem::take(&mut encoded.stripped);
+    Ok((save_encoded(images_dir, file_name, &key, &encoded)?, stripped))
+}
+
+/// Short hex digest of the source bytes and everything that affects the output
+fn content_key(data: &[u8], options: &ImageOptions) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(PIPELINE_VERSION.to_le_bytes());
+    hasher.update(options.max_width.to_le_bytes());
+    hasher.update([options.quality]);
+    for width in &options.variant_widths {
+        hasher.update(width.to_le_bytes());
+    }
+    hasher.update(options.max_animation_bytes.to_le_bytes());
+    hasher.update(data);
+
+    hasher.finalize()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The full-size file previously saved for `key`, whatever name it was uploaded with
+fn find_processed(images_dir: &Path, key: &str) -> Result<Option<PathBuf>, ImageError> {
+    let suffix = format!("_{}", key);
+    for entry in std::fs::read_dir(images_dir)? {
+        let path = entry?.path();
+        let extension = path.extension().and_then(|e| e.to_str());
+        if matches!(extension, Some("webp" | "mp4"))
+            && path
+                .file_stem()
+                .is_some_and(|stem| stem.to_string_lossy().ends_with(&suffix))
+        {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+/// Rebuild the [`SavedImage`] for an upload saved earlier
+fn load_saved(path: &Path, options: &ImageOptions) -> Result<SavedImage, ImageError> {
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let images_dir = path.parent().unwrap_or(Path::new("."));
+
+    // Videos are described by their poster
+    let is_video = path.extension().is_some_and(|e| e == "mp4");
+    let poster = is_video.then(|| images_dir.join(format!("{}-poster.webp", name)));
+    let img = image::open(poster.as_deref().unwrap_or(path)).map_err(|e| ImageError::Decode(e.to_string()))?;
+
+    let kind = if is_video {
+        MediaKind::Video
+    } else {
+        detect_kind(&std::fs::read(path)?)
+    };
+
+    let mut variants = Vec::new();
+    if kind == MediaKind::Image {
+        for &width in options.variant_widths.iter().filter(|&&w| w < img.width()) {
+            let variant_path = images_dir.join(format!("{}-{}w.webp", name, width));
+            if let Ok((width, height)) = image::image_dimensions(&variant_path) {
+                variants.push((variant_path, width, height));
+            }
+        }
+    }
+
+    Ok(SavedImage {
+        kind,
+        path: path.to_path_buf(),
+        width: img.width(),
+        height: img.height(),
+        variants,
+        poster,
+        blur_data_url: blur_data_url(&blur_placeholder(&img)?),
+    })
+}
+
+/// Save an encoded upload in `images_dir` as `<name>_<key>.webp` (or `.mp4`)
+///
+/// Variants are saved next to it as `<name>_<key>-<width>w.webp` and a video poster as
+/// `<name>_<key>-poster.webp`. The full-size file is written last and atomically, so its
+/// presence means the whole set is complete.
+fn save_encoded(
+    images_dir: &Path,
+    file_name: &str,
+    key: &str,
+    encoded: &EncodedImage,
+) -> Result<SavedImage, ImageError> {
     // Create a safe filename (slugify)
-    let stem = PathBuf::from(&file_name)
+    let stem = PathBuf::from(file_name)
         .file_stem()
         .map(|s| s.to_string_lossy().to_string())
         .unwrap_or_else(|| "image".to_string());
-    
-    let safe_name = slugify(&stem);
-    let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
-    let output_name = format!("{}_{}.webp", safe_name, timestamp);
-    
-    // Create temp file for input
-    let temp_dir = std::env::temp_dir();
-    let temp_input = temp_dir.join(&file_name);
-    let temp_output = temp_dir.join(&output_name);
-    
-    // Write input data to temp file
-    std::fs::write(&temp_input, &data)
-        .context("Failed to write temp input file")?;
-    
-    // Run ffmpeg to convert and compress
-    let output = Command::new("ffmpeg")
-        .args([
-            "-y",                           // Overwrite output
-            "-i", temp_input.to_str().unwrap(),
-            "-vf", "scale='min(1200,iw)':'-1'",  // Max width 1200px, maintain aspect ratio
-            "-quality", "80",               // WebP quality
-            "-compression_level", "6",      // Compression level
-            temp_output.to_str().unwrap(),
-        ])
-        .output()
-        .context("Failed to execute ffmpeg")?;
-    
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("ffmpeg failed: {}", stderr);
-    }
-    
-    // Move to blog's public images folder
-    let final_path = blog_path.join(&output_name);
-    std::fs::copy(&temp_output, &final_path)
-        .context("Failed to copy processed image to blog folder")?;
-    
-    // Cleanup temp files
-    let _ = std::fs::remove_file(&temp_input);
-    let _ = std::fs::remove_file(&temp_output);
-    
-    // Return the markdown-friendly path and local path
-    let markdown_path = format!("/images/posts/{}", output_name);
-    
-    Ok(ProcessImageResult {
-        path: markdown_path,
-        local_path: final_path.to_string_lossy().to_string(),
+    let name = format!("{}_{}", slugify(&stem), key);
+
+    let mut variants = Vec::new();
+    for (width, height, webp) in &encoded.variants {
+        let variant_path = images_dir.join(format!("{}-{}w.webp", name, width));
+        std::fs::write(&variant_path, webp)?;
+        variants.push((variant_path, *width, *height));
+    }
+
+    let poster = match &encoded.poster {
+        Some(webp) => {
+            let poster_path = images_dir.join(format!("{}-poster.webp", name));
+            std::fs::write(&poster_path, webp)?;
+            Some(poster_path)
+        }
+        None => None,
+    };
+
+    let path = images_dir.join(format!("{}.{}", name, encoded.extension()));
+    let mut temp = tempfile::NamedTempFile::new_in(images_dir)?;
+    temp.write_all(&encoded.data)?;
+    temp.persist(&path).map_err(|e| e.error)?;
+
+    Ok(SavedImage {
+        kind: encoded.kind,
+        path,
+        width: encoded.width,
+        height: encoded.height,
+        variants,
+        poster,
+        blur_data_url: blur_data_url(&encoded.placeholder),
+    })
+}
+
+fn blur_data_url(placeholder: &[u8]) -> String {
+    format!("data:image/webp;base64,{}", BASE64.encode(placeholder))
+}
+
+/// Decode, scale down to the configured max width and encode as lossy WebP, along with
+/// the narrower variants and the blur placeholder
+///
+/// Only pixels are encoded, so EXIF, XMP and ICC data never reach the output.
+fn encode_webp(data: &[u8], options: &ImageOptions) -> Result<EncodedImage, ImageError> {
+    let (img, stripped) = decode(data)?;
+    let img = resize_to_width(img, options.max_width, FilterType::Lanczos3);
+
+    // The encoder only accepts 8-bit RGB(A)
+    let img = if img.color().has_alpha() {
+        DynamicImage::ImageRgba8(img.to_rgba8())
+    } else {
+        DynamicImage::ImageRgb8(img.to_rgb8())
+    };
+
+    let mut variants = Vec::new();
+    for &width in options.variant_widths.iter().filter(|&&w| w < img.width()) {
+        let variant = resize_to_width(img.clone(), width, FilterType::Lanczos3);
+        variants.push((variant.width(), variant.height(), to_webp(&variant, options.quality)?));
+    }
+
+    Ok(EncodedImage {
+        kind: MediaKind::Image,
+        data: to_webp(&img, options.quality)?,
+        width: img.width(),
+        height: img.height(),
+        variants,
+        poster: None,
+        placeholder: blur_placeholder(&img)?,
+        stripped,
+    })
+}
+
+/// Encode an animated GIF, PNG or WebP as animated WebP, or as MP4 when the WebP would be
+/// over the size limit
+fn encode_animation(data: &[u8], options: &ImageOptions) -> Result<EncodedImage, ImageError> {
+    let frames = decode_frames(data)?;
+    let first = DynamicImage::ImageRgba8(frames[0].buffer().clone());
+    let target = resize_to_width(first.clone(), options.max_width, FilterType::Nearest);
+    let (width, height) = (target.width(), target.height());
+
+    // Resizing every frame with Lanczos is slow for long animations; Triangle is close enough
+    let mut timestamp = 0;
+    let mut resized = Vec::with_capacity(frames.len());
+    for frame in &frames {
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let buffer = image::imageops::resize(frame.buffer(), width, height, FilterType::Triangle);
+        resized.push((buffer, timestamp));
+        timestamp += (numer / denom.max(1)) as i32;
+    }
+
+    let mut config = webp::WebPConfig::new().map_err(|_| ImageError::Encode("invalid config".to_string()))?;
+    config.quality = options.quality as f32;
+    let mut encoder = webp::AnimEncoder::new(width, height, &config);
+    encoder.set_loop_count(0);
+    for (buffer, timestamp) in &resized {
+        encoder.add_frame(webp::AnimFrame::from_rgba(buffer, width, height, *timestamp));
+    }
+    let webp = encoder
+        .try_encode()
+        .map_err(|e| ImageError::Encode(format!("{:?}", e)))?
+        .to_vec();
+
+    let first = resize_to_width(first, options.max_width, FilterType::Lanczos3);
+    if webp.len() as u64 <= options.max_animation_bytes {
+        return Ok(EncodedImage {
+            kind: MediaKind::Animated,
+            data: webp,
+            width,
+            height,
+            variants: Vec::new(),
+            poster: None,
+            placeholder: blur_placeholder(&first)?,
+            stripped: StrippedMetadata::default(),
+        });
+    }
+
+    // Video codecs compress screen recordings far better than WebP
+    let mp4 = match transcode_mp4(data, options.max_width) {
+        Err(ImageError::UnsupportedFormat) => {
+            return Err(ImageError::TooLarge {
+                size: webp.len() as u64,
+                limit: options.max_animation_bytes,
+            })
+        }
+        result => result?,
+    };
+    video_result(mp4, first, options)
+}
+
+/// Transcode a video to MP4 with ffmpeg, with its first frame as the poster
+fn encode_video(data: &[u8], options: &ImageOptions) -> Result<EncodedImage, ImageError> {
+    let first = resize_to_width(decode_with_ffmpeg(data)?, options.max_width, FilterType::Lanczos3);
+    video_result(transcode_mp4(data, options.max_width)?, first, options)
+}
+
+fn video_result(
+    (mp4, stripped): (Vec<u8>, StrippedMetadata),
+    first: DynamicImage,
+    options: &ImageOptions,
+) -> Result<EncodedImage, ImageError> {
+    if mp4.len() as u64 > options.max_animation_bytes {
+        return Err(ImageError::TooLarge {
+            size: mp4.len() as u64,
+            limit: options.max_animation_bytes,
+        });
+    }
+
+    let first = DynamicImage::ImageRgb8(first.to_rgb8());
+    Ok(EncodedImage {
+        kind: MediaKind::Video,
+        data: mp4,
+        width: first.width(),
+        height: first.height(),
+        variants: Vec::new(),
+        poster: Some(to_webp(&first, options.quality)?),
+        placeholder: blur_placeholder(&first)?,
+        stripped,
     })
 }
 
-fn get_blog_images_path() -> Result<PathBuf> {
-    // Try to find the blog project root
-    // This assumes the editor is in the same monorepo as the blog
-    let current_dir = std::env::current_dir()?;
-    
-    // Look for the blog's public/images/posts directory
-    let possible_paths = [
-        current_dir.join("apps/blog/public/images/posts"),
-        current_dir.parent()
-            .and_then(|p| p.parent())
-            .map(|p| p.join("apps/blog/public/images/posts"))
-            .unwrap_or_default(),
-        // Fallback: try from home directory
-        dirs::home_dir()
-            .map(|h| h.join("Developments/HyoungMin/side-project/hyoungmin-tech-blog/apps/blog/public/images/posts"))
-            .unwrap_or_default(),
+/// Whether the upload is a still image, an animation or a video
+fn detect_kind(data: &[u8]) -> MediaKind {
+    if is_video(data) {
+        return MediaKind::Video;
+    }
+
+    let animated = match image::guess_format(data) {
+        Ok(ImageFormat::Gif) => GifDecoder::new(Cursor::new(data))
+            .is_ok_and(|decoder| decoder.into_frames().take(2).count() > 1),
+        Ok(ImageFormat::Png) => PngDecoder::new(Cursor::new(data))
+            .and_then(|decoder| decoder.is_apng())
+            .unwrap_or(false),
+        Ok(ImageFormat::WebP) => WebPDecoder::new(Cursor::new(data))
+            .is_ok_and(|decoder| decoder.has_animation()),
+        _ => false,
+    };
+
+    if animated {
+        MediaKind::Animated
+    } else {
+        MediaKind::Image
+    }
+}
+
+/// MP4/QuickTime (an `ftyp` box that isn't a HEIF or AVIF image) or WebM/Matroska
+fn is_video(data: &[u8]) -> bool {
+    const IMAGE_BRANDS: [&[u8]; 11] = [
+        b"heic", b"heix", b"hevc", b"heim", b"heis", b"hevm", b"hevs", b"mif1", b"msf1", b"avif", b"avis",
     ];
-    
-    for path in &possible_paths {
-        if path.exists() {
-            return Ok(path.clone());
+
+    match data.get(4..12) {
+        Some(header) if &header[..4] == b"ftyp" => !IMAGE_BRANDS.contains(&&header[4..]),
+        _ => data.starts_with(&[0x1a, 0x45, 0xdf, 0xa3]),
+    }
+}
+
+fn decode_frames(data: &[u8]) -> Result<Vec<Frame>, ImageError> {
+    let frames = match image::guess_format(data) {
+        Ok(ImageFormat::Gif) => GifDecoder::new(Cursor::new(data)).and_then(|d| d.into_frames().collect_frames()),
+        Ok(ImageFormat::Png) => PngDecoder::new(Cursor::new(data))
+            .and_then(|d| d.apng())
+            .and_then(|d| d.into_frames().collect_frames()),
+        Ok(ImageFormat::WebP) => WebPDecoder::new(Cursor::new(data)).and_then(|d| d.into_frames().collect_frames()),
+        _ => return Err(ImageError::Decode("not an animation".to_string())),
+    };
+
+    match frames {
+        Ok(frames) if !frames.is_empty() => Ok(frames),
+        Ok(_) => Err(ImageError::Decode("animation has no frames".to_string())),
+        Err(e) => Err(ImageError::Decode(e.to_string())),
+    }
+}
+
+/// Convert to a web-friendly MP4 no wider than `max_width`, without audio or metadata,
+/// along with the metadata that is dropped
+fn transcode_mp4(data: &[u8], max_width: u32) -> Result<(Vec<u8>, StrippedMetadata), ImageError> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_input = temp_dir.path().join("input");
+    let temp_output = temp_dir.path().join("output.mp4");
+    std::fs::write(&temp_input, data)?;
+
+    // Even dimensions are required by yuv420p
+    let scale = format!("scale='min({},iw)':-2,scale=trunc(iw/2)*2:trunc(ih/2)*2", max_width);
+    run_ffmpeg(
+        Command::new("ffmpeg")
+            .arg("-y")
+            .arg("-i")
+            .arg(&temp_input)
+            .args(["-map_metadata", "-1", "-an", "-vf", &scale])
+            .args(["-c:v", "libx264", "-pix_fmt", "yuv420p", "-crf", "28", "-preset", "slow"])
+            .args(["-movflags", "+faststart"])
+            .arg(&temp_output),
+    )?;
+
+    Ok((std::fs::read(&temp_output)?, probe_video_metadata(&temp_input)))
+}
+
+/// Tags every MP4 has, which say nothing about the recording
+const STRUCTURAL_TAGS: [&str; 4] = ["major_brand", "minor_version", "compatible_brands", "encoder"];
+
+/// Read a video's container tags with ffprobe; without ffprobe they are treated as absent,
+/// as `-map_metadata -1` drops them either way
+fn probe_video_metadata(input: &Path) -> StrippedMetadata {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries", "format_tags", "-of", "json"])
+        .arg(input)
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_probe_tags(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => StrippedMetadata::default(),
+    }
+}
+
+/// Metadata from `ffprobe -show_entries format_tags -of json` output
+fn parse_probe_tags(json: &str) -> StrippedMetadata {
+    let probe: serde_json::Value = serde_json::from_str(json).unwrap_or_default();
+    let mut tags: Vec<String> = probe["format"]["tags"]
+        .as_object()
+        .into_iter()
+        .flat_map(|tags| tags.keys())
+        .filter(|tag| !STRUCTURAL_TAGS.contains(&tag.as_str()))
+        .cloned()
+        .collect();
+    tags.sort();
+
+    StrippedMetadata {
+        // `location`, or `com.apple.quicktime.location.ISO6709` from iPhones
+        has_location: tags.iter().any(|tag| tag.to_lowercase().contains("location")),
+        exif_fields: tags,
+        ..Default::default()
+    }
+}
+
+fn blur_placeholder(img: &DynamicImage) -> Result<Vec<u8>, ImageError> {
+    let placeholder = resize_to_width(img.clone(), PLACEHOLDER_WIDTH, FilterType::Triangle);
+    to_webp(&placeholder, 50)
+}
+
+/// Scale down to `max_width`, keeping the aspect ratio; narrower images are left as is
+fn resize_to_width(img: DynamicImage, max_width: u32, filter: FilterType) -> DynamicImage {
+    if img.width() <= max_width {
+        return img;
+    }
+    let height = (img.height() as u64 * max_width as u64 / img.width() as u64).max(1);
+    img.resize_exact(max_width, height as u32, filter)
+}
+
+fn to_webp(img: &DynamicImage, quality: u8) -> Result<Vec<u8>, ImageError> {
+    let encoder = webp::Encoder::from_image(img).map_err(|e| ImageError::Encode(e.to_string()))?;
+    Ok(encoder.encode(quality as f32).to_vec())
+}
+
+/// Decode with the EXIF orientation applied to the pixels, along with the metadata
+/// that is dropped
+fn decode(data: &[u8]) -> Result<(DynamicImage, StrippedMetadata), ImageError> {
+    let mut decoder = match open_decoder(data) {
+        Ok(decoder) => decoder,
+        // Formats the image crate can't read (HEIC, AVIF, ...) go through ffmpeg if available
+        Err(image::ImageError::Unsupported(_)) => {
+            return Ok((decode_with_ffmpeg(data)?, StrippedMetadata::default()))
+        }
+        Err(e) => return Err(ImageError::Decode(e.to_string())),
+    };
+
+    let stripped = read_metadata(&mut decoder);
+    let orientation = decoder
+        .orientation()
+        .map_err(|e| ImageError::Decode(e.to_string()))?;
+    let mut img = DynamicImage::from_decoder(decoder).map_err(|e| ImageError::Decode(e.to_string()))?;
+    img.apply_orientation(orientation);
+
+    Ok((img, stripped))
+}
+
+fn inspect_metadata(data: &[u8]) -> Result<StrippedMetadata, ImageError> {
+    if is_video(data) {
+        let temp_dir = tempfile::tempdir()?;
+        let temp_input = temp_dir.path().join("input");
+        std::fs::write(&temp_input, data)?;
+        return Ok(probe_video_metadata(&temp_input));
+    }
+
+    match open_decoder(data) {
+        Ok(mut decoder) => Ok(read_metadata(&mut decoder)),
+        // ffmpeg drops the metadata of formats it has to convert
+        Err(image::ImageError::Unsupported(_)) => Ok(StrippedMetadata::default()),
+        Err(e) => Err(ImageError::Decode(e.to_string())),
+    }
+}
+
+fn open_decoder(data: &[u8]) -> image::ImageResult<impl ImageDecoder + '_> {
+    ImageReader::new(Cursor::new(data))
+        .with_guessed_format()?
+        .into_decoder()
+}
+
+/// Metadata chunks that can't be read are treated as absent; they are dropped either way
+fn read_metadata(decoder: &mut impl ImageDecoder) -> StrippedMetadata {
+    let mut stripped = StrippedMetadata::default();
+
+    if let Some(exif) = decoder.exif_metadata().ok().flatten() {
+        if let Ok(exif) = exif::Reader::new().read_raw(exif) {
+            stripped.orientation = exif
+                .get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+                .filter(|&orientation| orientation != 1);
+            stripped.has_location = exif.fields().any(|field| field.tag.0 == exif::Context::Gps);
+            stripped.exif_fields = exif.fields().map(|field| field.tag.to_string()).collect();
+            stripped.exif_fields.dedup();
         }
     }
-    
-    // If not found, create the first path option
-    let target = &possible_paths[0];
-    std::fs::create_dir_all(target)?;
-    Ok(target.clone())
+
+    if let Some(xmp) = decoder.xmp_metadata().ok().flatten() {
+        stripped.xmp = true;
+        let xmp = String::from_utf8_lossy(&xmp);
+        stripped.has_location |= xmp.contains("GPSLatitude") || xmp.contains("GPSLongitude");
+    }
+
+    stripped.icc_profile = decoder.icc_profile().ok().flatten().is_some();
+
+    stripped
+}
+
+/// Run an ffmpeg command; a missing ffmpeg means the format is unsupported
+fn run_ffmpeg(command: &mut Command) -> Result<(), ImageError> {
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(ImageError::UnsupportedFormat)
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last_line = stderr.lines().last().unwrap_or_default();
+        return Err(ImageError::Ffmpeg(last_line.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Convert the first frame to PNG with ffmpeg in a private temp directory, then decode that
+fn decode_with_ffmpeg(data: &[u8]) -> Result<DynamicImage, ImageError> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_input = temp_dir.path().join("input");
+    let temp_output = temp_dir.path().join("output.png");
+    std::fs::write(&temp_input, data)?;
+
+    run_ffmpeg(
+        Command::new("ffmpeg")
+            .arg("-y")
+            .arg("-i")
+            .arg(&temp_input)
+            .args(["-frames:v", "1"])
+            .arg(&temp_output),
+    )?;
+
+    let png = std::fs::read(&temp_output)?;
+    image::load_from_memory_with_format(&png, ImageFormat::Png)
+        .map_err(|e| ImageError::Decode(e.to_string()))
 }
 
 fn slugify(s: &str) -> String {
     s.chars()
         .map(|c| {
             if c.is_ascii_alphanumeric() {
-                c.to_ascii_lowercase()
+                c.to_ascii_lowercase().to_string()
+            } else if let Some(romanized) = crate::slug::romanize_syllable(c) {
+                romanized
             } else if c == ' ' || c == '-' || c == '_' {
-                '-'
+                "-".to_string()
             } else {
-                '_'
+                "_".to_string()
             }
         })
         .collect::<String>()
@@ -141,7 +792,186 @@ mod tests {
     fn test_slugify() {
         assert_eq!(slugify("Hello World"), "hello-world");
         assert_eq!(slugify("Test_Image-01"), "test-image-01");
-        assert_eq!(slugify("한글 이미지"), "___-__");
+        assert_eq!(slugify("한글 이미지"), "hangeul-imiji");
+    }
+
+    #[test]
+    fn test_encode_and_save_image() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        let mut png = Vec::new();
+        DynamicImage::new_rgb8(2000, 1000)
+            .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        let options = ImageOptions {
+            variant_widths: vec![480, 768, 1600],
+            ..Default::default()
+        };
+
+        let encoded = encode_webp(&png, &options).unwrap();
+        assert_eq!((encoded.width, encoded.height), (1200, 600));
+        let widths: Vec<_> = encoded.variants.iter().map(|(w, h, _)| (*w, *h)).collect();
+        assert_eq!(widths, [(480, 240), (768, 384)]);
+
+        let key = content_key(&png, &options);
+        let saved = save_encoded(dir, "큰 사진.png", &key, &encoded).unwrap();
+        let name = format!("keun-sajin_{}", key);
+        assert_eq!(saved.path, dir.join(format!("{}.webp", name)));
+        assert_eq!(saved.variants[0].0, dir.join(format!("{}-480w.webp", name)));
+        assert!(saved.blur_data_url.starts_with("data:image/webp;base64,"));
+
+        let webp = std::fs::read(&saved.path).unwrap();
+        assert_eq!(image::guess_format(&webp).unwrap(), ImageFormat::WebP);
+        let img = image::load_from_memory(&webp).unwrap();
+        assert_eq!((img.width(), img.height()), (1200, 600));
+        let placeholder = image::load_from_memory(&encoded.placeholder).unwrap();
+        assert_eq!((placeholder.width(), placeholder.height()), (16, 8));
+
+        assert!(matches!(
+            encode_webp(b"\x89PNG\r\n\x1a\nnot really", &options),
+            Err(ImageError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn test_process_into_reuses_identical_uploads() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        let mut png = Vec::new();
+        DynamicImage::new_rgba8(1000, 500)
+            .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        let options = ImageOptions::default();
+
+        let (first, _) = process_into(dir, "screenshot.png", &png, &options).unwrap();
+        let modified = std::fs::metadata(&first.path).unwrap().modified().unwrap();
+
+        // Same bytes under another name: nothing is written
+        let (again, _) = process_into(dir, "copy.png", &png, &options).unwrap();
+        assert_eq!(again.path, first.path);
+        assert_eq!((again.width, again.height), (first.width, first.height));
+        assert_eq!(again.variants, first.variants);
+        assert_eq!(std::fs::metadata(&first.path).unwrap().modified().unwrap(), modified);
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 3);
+
+        // Different settings produce a different image
+        let options = ImageOptions { quality: 60, ..options };
+        let (other, _) = process_into(dir, "screenshot.png", &png, &options).unwrap();
+        assert_ne!(other.path, first.path);
+    }
+
+    #[test]
+    fn test_orientation_applied_and_metadata_stripped() {
+        // TIFF with IFD0 { Orientation = 6 (rotate 90° clockwise), Model, GPS pointer }
+        // and a GPS IFD { GPSLatitudeRef = "N" }
+        let mut tiff: Vec<u8> = b"II*\0\x08\0\0\0".to_vec();
+        tiff.extend([3, 0]);
+        tiff.extend([0x12, 0x01, 3, 0, 1, 0, 0, 0, 6, 0, 0, 0]);
+        tiff.extend([0x10, 0x01, 2, 0, 4, 0, 0, 0, b'P', b'h', b'n', 0]);
+        tiff.extend([0x25, 0x88, 4, 0, 1, 0, 0, 0, 50, 0, 0, 0]);
+        tiff.extend([0, 0, 0, 0]);
+        tiff.extend([1, 0]);
+        tiff.extend([0x01, 0x00, 2, 0, 2, 0, 0, 0, b'N', 0, 0, 0]);
+        tiff.extend([0, 0, 0, 0]);
+
+        let mut jpeg = Vec::new();
+        let mut encoder = image::codecs::jpeg::JpegEncoder::new(&mut jpeg);
+        image::ImageEncoder::set_exif_metadata(&mut encoder, tiff).unwrap();
+        DynamicImage::new_rgb8(40, 20).write_with_encoder(encoder).unwrap();
+
+        let stripped = inspect_metadata(&jpeg).unwrap();
+        assert_eq!(stripped.orientation, Some(6));
+        assert!(stripped.has_location);
+        assert_eq!(stripped.exif_fields, ["Orientation", "Model", "GPSLatitudeRef"]);
+        assert!(!stripped.xmp && !stripped.icc_profile);
+
+        let encoded = encode_webp(&jpeg, &ImageOptions::default()).unwrap();
+        assert_eq!((encoded.width, encoded.height), (20, 40));
+        assert_eq!(encoded.stripped, stripped);
+
+        // A WebP with metadata would be an extended (VP8X) file with EXIF/XMP/ICCP chunks
+        assert_eq!(&encoded.data[12..15], b"VP8");
+        assert_ne!(&encoded.data[12..16], b"VP8X");
+        let mut decoder = open_decoder(&encoded.data).unwrap();
+        assert_eq!(read_metadata(&mut decoder), StrippedMetadata::default());
     }
-}
 
+    #[test]
+    fn test_parse_probe_tags() {
+        let json = r#"{
+            "format": {
+                "tags": {
+                    "major_brand": "qt  ",
+                    "minor_version": "0",
+                    "compatible_brands": "qt  ",
+                    "creation_time": "2024-05-01T09:30:00.000000Z",
+                    "com.apple.quicktime.location.ISO6709": "+37.5665+126.9780+038.000/",
+                    "com.apple.quicktime.model": "iPhone 15"
+                }
+            }
+        }"#;
+        let stripped = parse_probe_tags(json);
+        assert!(stripped.has_location);
+        assert_eq!(
+            stripped.exif_fields,
+            [
+                "com.apple.quicktime.location.ISO6709",
+                "com.apple.quicktime.model",
+                "creation_time",
+            ]
+        );
+
+        let stripped = parse_probe_tags(r#"{"format": {"tags": {"major_brand": "isom", "encoder": "Lavf60"}}}"#);
+        assert_eq!(stripped, StrippedMetadata::default());
+        assert_eq!(parse_probe_tags(""), StrippedMetadata::default());
+    }
+
+    #[test]
+    fn test_animated_gif() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        let frames: Vec<Frame> = [[255, 0, 0, 255], [0, 0, 255, 255], [0, 255, 0, 255]]
+            .into_iter()
+            .map(|color| {
+                let buffer = image::RgbaImage::from_pixel(1600, 400, image::Rgba(color));
+                Frame::from_parts(buffer, 0, 0, image::Delay::from_numer_denom_ms(100, 1))
+            })
+            .collect();
+        let mut gif = Vec::new();
+        image::codecs::gif::GifEncoder::new(&mut gif).encode_frames(frames).unwrap();
+
+        let mut still = Vec::new();
+        DynamicImage::new_rgb8(10, 10)
+            .write_to(&mut Cursor::new(&mut still), ImageFormat::Gif)
+            .unwrap();
+        assert_eq!(detect_kind(&gif), MediaKind::Animated);
+        assert_eq!(detect_kind(&still), MediaKind::Image);
+        assert_eq!(detect_kind(b"\0\0\0\x18ftypmp42\0\0\0\0"), MediaKind::Video);
+        assert_eq!(detect_kind(b"\0\0\0\x18ftypheic\0\0\0\0"), MediaKind::Image);
+
+        let options = ImageOptions::default();
+        let (saved, _) = process_into(dir, "demo.gif", &gif, &options).unwrap();
+        assert_eq!(saved.kind, MediaKind::Animated);
+        assert_eq!((saved.width, saved.height), (1200, 300));
+        assert!(saved.variants.is_empty());
+        let webp = std::fs::read(&saved.path).unwrap();
+        assert_eq!(detect_kind(&webp), MediaKind::Animated);
+        assert_eq!(decode_frames(&webp).unwrap().len(), 3);
+
+        // Reused uploads keep their kind
+        let (again, _) = process_into(dir, "demo.gif", &gif, &options).unwrap();
+        assert_eq!((again.kind, again.path), (MediaKind::Animated, saved.path));
+
+        let options = ImageOptions {
+            max_animation_bytes: 10,
+            ..options
+        };
+        assert!(matches!(
+            process_into(dir, "demo.gif", &gif, &options),
+            Err(ImageError::TooLarge { limit: 10, .. })
+        ));
+    }
+}
@@ -0,0 +1,443 @@
+use anyhow::Result;
+use markdown::mdast::{AttributeContent, AttributeValue, Node};
+use markdown::unist::Position;
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::command;
+
+use super::settings::{load_workspace, Workspace};
+use super::validate::{Diagnostic, Rule, Severity};
+
+const MAX_SUGGESTIONS: usize = 3;
+
+/// First path segments the blog serves itself; any other single segment is a post slug
+const APP_ROUTES: &[&str] = &[
+    "posts",
+    "tags",
+    "series",
+    "categories",
+    "portfolio",
+    "feed.xml",
+    "robots.txt",
+    "sitemap.xml",
+    "favicon.ico",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkProblem {
+    /// `/<slug>` with no such post
+    MissingPost,
+    /// `/<slug>` pointing at a post the blog doesn't show
+    DraftPost,
+    /// `/posts/<slug>`; posts are served at `/<slug>` and `/posts` is only the post list
+    WrongRoute,
+    /// An image or other public file that doesn't exist
+    MissingFile,
+}
+
+/// A link in post content that doesn't resolve
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BrokenLink {
+    pub url: String,
+    pub problem: LinkProblem,
+    /// 1-based line in the content, without frontmatter
+    pub line: usize,
+    /// 1-based column
+    pub column: usize,
+    /// Existing posts or images with similar names, closest first
+    pub suggestions: Vec<String>,
+}
+
+impl BrokenLink {
+    /// Report the link alongside the MDX problems; only links to drafts are let through
+    pub(crate) fn diagnostic(&self) -> Diagnostic {
+        let (severity, reason) = match self.problem {
+            LinkProblem::MissingPost => (Severity::Error, "no such post"),
+            LinkProblem::DraftPost => (Severity::Warning, "the post is a draft"),
+            LinkProblem::WrongRoute => (Severity::Error, "posts are served at /<slug>"),
+            LinkProblem::MissingFile => (Severity::Error, "no such file"),
+        };
+        let mut message = format!("Broken link {}: {}", self.url, reason);
+        if !self.suggestions.is_empty() {
+            message.push_str(&format!("; did you mean {}?", self.suggestions.join(" or ")));
+        }
+        Diagnostic {
+            severity,
+            rule: Rule::BrokenLink,
+            message,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+/// Check the post and image links in post content against the posts and images directories
+///
+/// `slug` is the post being edited, which may link to itself before it's written.
+/// References to local images that submitting will publish are skipped.
+#[command]
+pub async fn check_links(
+    content: String,
+    slug: Option<String>,
+    local_images: Option<HashMap<String, String>>,
+) -> Result<Vec<BrokenLink>, String> {
+    load_workspace()
+        .and_then(|workspace| {
+            check_links_internal(
+                &workspace,
+                &content,
+                slug.as_deref(),
+                &local_images.unwrap_or_default(),
+            )
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Resolve every site-relative link in `content`
+///
+/// `/<slug>` must be a published post and URLs under the public URL prefix must be files in
+/// the images directory. Other paths with a file extension are looked up in the blog's public
+/// directory. The blog's own routes, such as `/tags/<tag>`, aren't checked, except that
+/// `/posts/<slug>` is reported as the wrong route for a post. Paths are percent-decoded first.
+pub(crate) fn check_links_internal(
+    workspace: &Workspace,
+    content: &str,
+    slug: Option<&str>,
+    local_images: &HashMap<String, String>,
+) -> Result<Vec<BrokenLink>> {
+    let links = find_links(content)?;
+    if links.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let posts = super::posts::scan_posts(&workspace.posts_dir)?;
+    let published: Vec<&str> = posts
+        .iter()
+        .filter(|post| !post.metadata.draft)
+        .map(|post| post.slug.as_str())
+        .collect();
+    let public_dir = workspace.public_dir();
+    let mut images: Option<Vec<String>> = None;
+
+    let mut broken = Vec::new();
+    for (url, position) in links {
+        // Submitting publishes these, or reports them when the file is missing
+        let local = local_images.contains_key(&url)
+            || matches!(super::github::resolve_local_image(workspace, &url, local_images), Some(Ok(_)));
+        if local {
+            continue;
+        }
+        let path = percent_decode(url.split(['#', '?']).next().unwrap_or_default());
+        if !path.starts_with('/') || path.starts_with("//") {
+            continue;
+        }
+        let post_suggestions = |target: &str| -> Vec<String> {
+            similar(target, published.iter().copied())
+                .into_iter()
+                .map(|slug| format!("/{}", slug))
+                .collect()
+        };
+
+        let (problem, suggestions) = if let Some(target) = post_list_route(&path) {
+            let suggestions = if Some(target) == slug || posts.iter().any(|post| post.slug == target) {
+                vec![format!("/{}", target)]
+            } else {
+                post_suggestions(target)
+            };
+            (LinkProblem::WrongRoute, suggestions)
+        } else if let Some(file) = workspace.image_file(&path) {
+            if file.is_file() {
+                continue;
+            }
+            let names = match &mut images {
+                Some(names) => names,
+                None => images.insert(list_images(workspace)?),
+            };
+            let name = path[workspace.public_url_prefix.len() + 1..].to_string();
+            let suggestions = similar(&name, names.iter().map(String::as_str))
+                .into_iter()
+                .map(|name| workspace.image_url(name))
+                .collect();
+            (LinkProblem::MissingFile, suggestions)
+        } else if let Some(target) = post_route(&path) {
+            if Some(target) == slug || published.contains(&target) {
+                continue;
+            }
+            let problem = if posts.iter().any(|post| post.slug == target) {
+                LinkProblem::DraftPost
+            } else {
+                LinkProblem::MissingPost
+            };
+            (problem, post_suggestions(target))
+        } else {
+            let relative = path.trim_start_matches('/');
+            let first = relative.split('/').next().unwrap_or_default();
+            let has_extension = path.rsplit('/').next().is_some_and(|name| name.contains('.'));
+            let Some(public_dir) = public_dir.as_ref().filter(|_| has_extension) else {
+                continue;
+            };
+            if APP_ROUTES.contains(&first)
+                || relative.split('/').any(|part| part == "..")
+                || public_dir.join(relative).is_file()
+            {
+                continue;
+            }
+            (LinkProblem::MissingFile, Vec::new())
+        };
+
+        let (line, column) = position.map_or((1, 1), |p| (p.start.line, p.start.column));
+        broken.push(BrokenLink {
+            url,
+            problem,
+            line,
+            column,
+            suggestions,
+        });
+    }
+
+    Ok(broken)
+}
+
+/// The slug in `/posts/<slug>`
+fn post_list_route(path: &str) -> Option<&str> {
+    let target = path.strip_prefix("/posts/")?.trim_end_matches('/');
+    (!target.is_empty() && !target.contains('/')).then_some(target)
+}
+
+/// The slug in `/<slug>`, unless the path is one of the blog's own routes or a file
+fn post_route(path: &str) -> Option<&str> {
+    let target = path.strip_prefix('/')?.trim_end_matches('/');
+    let is_post = !target.is_empty()
+        && !target.contains(['/', '.'])
+        && !APP_ROUTES.contains(&target);
+    is_post.then_some(target)
+}
+
+/// Decode `%XX` escapes, as the blog does before looking up a route or file
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit));
+        match hex {
+            Some(hex) => {
+                // Two ASCII hex digits always parse
+                let hex = std::str::from_utf8(hex).unwrap_or_default();
+                decoded.push(u8::from_str_radix(hex, 16).unwrap_or_default());
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Link, image and definition URLs and the `href`, `src`, `poster` and `srcSet` attributes
+/// of JSX elements, with where they appear; code is skipped
+fn find_links(content: &str) -> Result<Vec<(String, Option<Position>)>> {
+    let tree = markdown::to_mdast(content, &super::validate::parse_options())
+        .map_err(|message| anyhow::anyhow!("Content doesn't parse as MDX: {}", message))?;
+
+    let mut links = Vec::new();
+    collect_links(&tree, &mut links);
+    Ok(links)
+}
+
+fn collect_links(node: &Node, links: &mut Vec<(String, Option<Position>)>) {
+    match node {
+        Node::Link(link) => links.push((link.url.clone(), link.position.clone())),
+        Node::Image(image) => links.push((image.url.clone(), image.position.clone())),
+        Node::Definition(definition) => {
+            links.push((definition.url.clone(), definition.position.clone()))
+        }
+        Node::MdxJsxFlowElement(element) => {
+            collect_attribute_links(&element.attributes, &element.position, links)
+        }
+        Node::MdxJsxTextElement(element) => {
+            collect_attribute_links(&element.attributes, &element.position, links)
+        }
+        _ => {}
+    }
+
+    for child in node.children().into_iter().flatten() {
+        collect_links(child, links);
+    }
+}
+
+fn collect_attribute_links(
+    attributes: &[AttributeContent],
+    position: &Option<Position>,
+    links: &mut Vec<(String, Option<Position>)>,
+) {
+    for attribute in attributes {
+        let AttributeContent::Property(property) = attribute else {
+            continue;
+        };
+        let Some(AttributeValue::Literal(value)) = &property.value else {
+            continue;
+        };
+        match property.name.as_str() {
+            "href" | "src" | "poster" => links.push((value.clone(), position.clone())),
+            // Each candidate is a URL followed by an optional descriptor
+            "srcSet" => links.extend(
+                value
+                    .split(',')
+                    .filter_map(|candidate| candidate.split_whitespace().next())
+                    .map(|url| (url.to_string(), position.clone())),
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// Files in the images directory, relative to it
+fn list_images(workspace: &Workspace) -> Result<Vec<String>> {
+    if !workspace.images_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    super::orphans::list_files(&workspace.images_dir)?
+        .iter()
+        .map(|path| {
+            Ok(path
+                .strip_prefix(&workspace.images_dir)?
+                .to_string_lossy()
+                .replace('\\', "/"))
+        })
+        .collect()
+}
+
+/// Candidates within a few edits of `name`, or that only add or drop a suffix like `-2`
+fn similar<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(2);
+
+    let mut scored: Vec<(usize, &str)> = candidates
+        .filter_map(|candidate| {
+            let distance = edit_distance(name, candidate);
+            let (shorter, longer) = if candidate.len() < name.len() {
+                (candidate, name)
+            } else {
+                (name, candidate)
+            };
+            let extended = shorter.len() >= 4 && longer.starts_with(shorter);
+            (distance <= max_distance || extended).then_some((distance, candidate))
+        })
+        .collect();
+    scored.sort();
+
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Levenshtein distance in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + usize::from(ca != cb)).min(row[j] + 1).min(above + 1);
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::settings::test_workspace;
+
+    #[test]
+    fn test_check_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = test_workspace(&dir);
+        for (slug, draft) in [("react-hooks-guide", false), ("nextjs-i18n", false), ("wip", true)] {
+            std::fs::write(
+                workspace.posts_dir.join(format!("{}.mdx", slug)),
+                format!("---\ntitle: {}\ndescription: d\ncreatedAt: 2024-01-01\ndraft: {}\n---\n", slug, draft),
+            )
+            .unwrap();
+        }
+        for name in ["diagram.webp", "diagram-480w.webp", "café.webp"] {
+            std::fs::write(workspace.images_dir.join(name), name).unwrap();
+        }
+        std::fs::write(workspace.root.join("public/resume.pdf"), "pdf").unwrap();
+
+        let content = "See [hooks](/react-hooks-guide#state), [i18n](/nextjs-i18n/), [self](/new-post), \
+                       [tags](/tags/react), [list](/posts), [feed](/feed.xml) and [cv](/resume.pdf).\n\n\
+                       Old [hooks](/react-hook-guide) and [wip][wip] and [legacy](/posts/nextjs-i18n).\n\n\
+                       ![a](/images/posts/diagram.webp) ![b](/images/posts/diagam.webp) ![c](/images/posts/caf%C3%A9.webp)\n\n\
+                       <img src=\"/images/posts/diagram.webp\" srcSet=\"/images/posts/diagram-320w.webp 320w\" />\n\n\
+                       [gone](/old.pdf) [ext](https://example.com/x.pdf) ![shot](/Users/me/shot.png)\n\n\
+                       ```md\n[in code](/nowhere)\n```\n\n\
+                       [wip]: /wip\n";
+        let local_images = HashMap::from([("/Users/me/shot.png".to_string(), "/tmp/shot.png".to_string())]);
+
+        let broken = check_links_internal(&workspace, content, Some("new-post"), &local_images).unwrap();
+        let found: Vec<_> = broken
+            .iter()
+            .map(|link| (link.url.as_str(), link.problem, link.line, link.column))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("/react-hook-guide", LinkProblem::MissingPost, 3, 5),
+                ("/posts/nextjs-i18n", LinkProblem::WrongRoute, 3, 51),
+                ("/images/posts/diagam.webp", LinkProblem::MissingFile, 5, 34),
+                ("/images/posts/diagram-320w.webp", LinkProblem::MissingFile, 7, 1),
+                ("/old.pdf", LinkProblem::MissingFile, 9, 1),
+                ("/wip", LinkProblem::DraftPost, 15, 1),
+            ]
+        );
+        assert_eq!(broken[0].suggestions, ["/react-hooks-guide"]);
+        assert_eq!(broken[1].suggestions, ["/nextjs-i18n"]);
+        assert_eq!(broken[2].suggestions, ["/images/posts/diagram.webp"]);
+        assert_eq!(
+            broken[3].suggestions,
+            ["/images/posts/diagram-480w.webp", "/images/posts/diagram.webp"]
+        );
+        assert!(broken[4].suggestions.is_empty());
+
+        let diagnostic = broken[0].diagnostic();
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(
+            diagnostic.to_string(),
+            "3:5: Broken link /react-hook-guide: no such post; did you mean /react-hooks-guide?"
+        );
+        assert_eq!(
+            broken[1].diagnostic().to_string(),
+            "3:51: Broken link /posts/nextjs-i18n: posts are served at /<slug>; did you mean /nextjs-i18n?"
+        );
+        assert_eq!(broken[5].diagnostic().severity, Severity::Warning);
+
+        assert_eq!(percent_decode("/images/posts/a%20b%EA%B0%80.png"), "/images/posts/a b가.png");
+        assert_eq!(percent_decode("/100%/%zz%2"), "/100%/%zz%2");
+    }
+
+    #[test]
+    fn test_similar() {
+        let slugs = ["react-hooks-guide", "react-hooks-guide-2", "vue-guide", "hooks"];
+        assert_eq!(
+            similar("react-hook-guide", slugs.into_iter()),
+            ["react-hooks-guide", "react-hooks-guide-2"]
+        );
+        assert_eq!(similar("react-hooks", slugs.into_iter()), ["react-hooks-guide", "react-hooks-guide-2"]);
+        assert!(similar("typescript", slugs.into_iter()).is_empty());
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
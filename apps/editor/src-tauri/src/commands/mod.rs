@@ -1,2 +1,12 @@
+pub mod catalog;
+pub mod cover;
 pub mod image;
 pub mod github;
+pub mod history;
+pub mod links;
+pub mod orphans;
+pub mod posts;
+pub mod queue;
+pub mod series;
+pub mod settings;
+pub mod validate;
@@ -0,0 +1,254 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+use super::github::{get_drafts_dir, read_draft};
+use super::settings::{load_workspace, Workspace};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OrphanedImage {
+    /// Path relative to the images directory
+    pub name: String,
+    /// Public URL, e.g. `/images/posts/a.webp`
+    pub url: String,
+    /// Size in bytes
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrphanedImages {
+    pub images: Vec<OrphanedImage>,
+    /// Combined size in bytes
+    #[serde(rename = "totalSize")]
+    pub total_size: u64,
+}
+
+/// List files in the images directory that no post or draft references
+#[command]
+pub async fn find_orphaned_images() -> Result<OrphanedImages, String> {
+    load_workspace()
+        .and_then(|workspace| find_orphaned_images_internal(&workspace, &get_drafts_dir()?))
+        .map_err(|e| e.to_string())
+}
+
+/// Delete the selected orphaned images and return what was deleted
+///
+/// The deletions are committed together with the next post change that is published.
+#[command]
+pub async fn delete_orphaned_images(names: Vec<String>) -> Result<OrphanedImages, String> {
+    load_workspace()
+        .and_then(|workspace| delete_orphaned_images_internal(&workspace, &get_drafts_dir()?, &names))
+        .map_err(|e| e.to_string())
+}
+
+fn find_orphaned_images_internal(workspace: &Workspace, drafts_dir: &Path) -> Result<OrphanedImages> {
+    let referenced = referenced_images(workspace, drafts_dir, None)?;
+
+    let mut images = Vec::new();
+    for path in list_files(&workspace.images_dir)? {
+        if referenced.contains(&path) {
+            continue;
+        }
+        let name = path
+            .strip_prefix(&workspace.images_dir)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        images.push(OrphanedImage {
+            url: workspace.image_url(&name),
+            size: std::fs::metadata(&path)?.len(),
+            name,
+        });
+    }
+    images.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(OrphanedImages {
+        total_size: images.iter().map(|image| image.size).sum(),
+        images,
+    })
+}
+
+fn delete_orphaned_images_internal(
+    workspace: &Workspace,
+    drafts_dir: &Path,
+    names: &[String],
+) -> Result<OrphanedImages> {
+    // Check again right before deleting; a post may have started using an image since
+    let orphans = find_orphaned_images_internal(workspace, drafts_dir)?.images;
+
+    let mut selected = Vec::new();
+    for name in names {
+        match orphans.iter().position(|image| &image.name == name) {
+            Some(i) => selected.push(i),
+            None => anyhow::bail!("'{}' is not an orphaned image", name),
+        }
+    }
+    selected.sort_unstable();
+    selected.dedup();
+
+    let mut orphans: Vec<Option<OrphanedImage>> = orphans.into_iter().map(Some).collect();
+    let mut deleted = Vec::new();
+    for i in selected {
+        let image = orphans[i].take().expect("selected indices are unique");
+        let path = workspace.images_dir.join(&image.name);
+        std::fs::remove_file(&path).with_context(|| format!("Failed to delete {:?}", path))?;
+        println!("Deleted orphaned image: {:?}", path);
+        deleted.push(image);
+    }
+
+    Ok(OrphanedImages {
+        total_size: deleted.iter().map(|image| image.size).sum(),
+        images: deleted,
+    })
+}
+
+/// Files in the images directory the post `slug` mentions and no other post or saved
+/// draft does
+///
+/// A saved draft of the post itself still counts, since it may be submitted again.
+pub(crate) fn images_only_used_by(workspace: &Workspace, drafts_dir: &Path, slug: &str) -> Result<Vec<PathBuf>> {
+    let post_path = workspace.posts_dir.join(format!("{}.mdx", slug));
+    let source = std::fs::read_to_string(&post_path)
+        .with_context(|| format!("Failed to read {:?}", post_path))?;
+    let used_elsewhere = referenced_images(workspace, drafts_dir, Some(&post_path))?;
+
+    let mut images: Vec<PathBuf> = crate::markdown::find_urls_with_prefix(&source, &workspace.public_url_prefix)
+        .iter()
+        .filter_map(|url| workspace.image_file(url))
+        .filter(|path| path.is_file() && !used_elsewhere.contains(path))
+        .collect();
+    images.sort();
+    images.dedup();
+
+    Ok(images)
+}
+
+/// Files in the images directory mentioned by any post but `skip_post`, or by a saved draft
+///
+/// Posts are searched as raw text, so the `cover` frontmatter, plain links to files and
+/// posts whose frontmatter doesn't parse all count.
+fn referenced_images(
+    workspace: &Workspace,
+    drafts_dir: &Path,
+    skip_post: Option<&Path>,
+) -> Result<HashSet<PathBuf>> {
+    let prefix = &workspace.public_url_prefix;
+    let mut urls = Vec::new();
+
+    for entry in std::fs::read_dir(&workspace.posts_dir).context("Failed to read posts directory")? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("mdx") || Some(path.as_path()) == skip_post {
+            continue;
+        }
+
+        let source = std::fs::read_to_string(&path)?;
+        urls.extend(crate::markdown::find_urls_with_prefix(&source, prefix));
+    }
+
+    if drafts_dir.is_dir() {
+        for entry in std::fs::read_dir(drafts_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            match read_draft(&path) {
+                Ok(draft) => {
+                    urls.extend(crate::markdown::find_urls_with_prefix(&draft.content, prefix));
+                    urls.extend(draft.metadata.cover);
+                }
+                Err(e) => println!("Skipping {:?}: {:#}", path, e),
+            }
+        }
+    }
+
+    Ok(urls
+        .iter()
+        .filter_map(|url| workspace.image_file(url))
+        .collect())
+}
+
+/// Every file under `dir`, skipping hidden files such as `.gitkeep`
+pub(crate) fn list_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).with_context(|| format!("Failed to read {:?}", dir))? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            if entry.file_type()?.is_dir() {
+                pending.push(entry.path());
+            } else {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::settings::test_workspace;
+
+    #[test]
+    fn test_find_and_delete_orphaned_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = test_workspace(&dir);
+        let posts_dir = workspace.posts_dir.clone();
+        let images_dir = workspace.images_dir.clone();
+        let drafts_dir = workspace.root.join("drafts");
+        std::fs::create_dir_all(images_dir.join("old")).unwrap();
+        std::fs::create_dir_all(&drafts_dir).unwrap();
+
+        for name in ["used.webp", "used-480w.webp", "cover.webp", "draft.webp", "old/unused.webp", ".gitkeep"] {
+            std::fs::write(images_dir.join(name), name).unwrap();
+        }
+        std::fs::write(images_dir.join("unused.webp"), "0123456789").unwrap();
+        std::fs::write(
+            posts_dir.join("post.mdx"),
+            "---\ntitle: Post\ndescription: d\ncreatedAt: 2024-01-01\ncover: /images/posts/cover.webp\n---\n\n\
+             <img src=\"/images/posts/used.webp\" srcSet=\"/images/posts/used-480w.webp 480w\" />\n",
+        )
+        .unwrap();
+        std::fs::write(
+            drafts_dir.join("draft.json"),
+            r#"{"metadata":{"title":"Draft","description":"","createdAt":"2024-01-01"},"content":"![a](/images/posts/draft.webp)"}"#,
+        )
+        .unwrap();
+
+        let orphans = find_orphaned_images_internal(&workspace, &drafts_dir).unwrap();
+        let names: Vec<_> = orphans.images.iter().map(|image| image.name.as_str()).collect();
+        assert_eq!(names, ["old/unused.webp", "unused.webp"]);
+        assert_eq!(orphans.images[1].url, "/images/posts/unused.webp");
+        assert_eq!(orphans.total_size, 15 + 10);
+
+        let err = delete_orphaned_images_internal(&workspace, &drafts_dir, &["used.webp".to_string()]);
+        assert!(err.is_err());
+        assert!(images_dir.join("used.webp").exists());
+
+        let deleted = delete_orphaned_images_internal(&workspace, &drafts_dir, &["unused.webp".to_string()]).unwrap();
+        assert_eq!(deleted.total_size, 10);
+        assert!(!images_dir.join("unused.webp").exists());
+        assert!(images_dir.join("old/unused.webp").exists());
+
+        // Plain links count, even in a post whose frontmatter doesn't parse
+        std::fs::write(
+            posts_dir.join("broken.mdx"),
+            "---\ntitle: [unclosed\n---\n[download](/images/posts/old/unused.webp)\n",
+        )
+        .unwrap();
+        let orphans = find_orphaned_images_internal(&workspace, &drafts_dir).unwrap();
+        assert!(orphans.images.is_empty());
+
+        // Deleting a post takes only the images nothing else mentions
+        std::fs::write(posts_dir.join("other.mdx"), "---\ntitle: Other\n---\n![c](/images/posts/cover.webp)\n").unwrap();
+        let only_used = images_only_used_by(&workspace, &drafts_dir, "post").unwrap();
+        assert_eq!(only_used, [images_dir.join("used-480w.webp"), images_dir.join("used.webp")]);
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+use tauri::command;
+
+use super::github::{get_posts_dir, is_valid_slug, PostMetadata};
+
+#[derive(Debug, Serialize)]
+pub struct PostSummary {
+    /// File stem of the `.mdx` file
+    pub slug: String,
+    pub title: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    pub draft: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Post {
+    pub slug: String,
+    pub metadata: PostMetadata,
+    pub content: String,
+}
+
+/// List published posts, newest first
+#[command]
+pub async fn list_posts() -> Result<Vec<PostSummary>, String> {
+    get_posts_dir()
+        .and_then(|dir| list_posts_internal(&dir))
+        .map_err(|e| e.to_string())
+}
+
+/// Load a published post for editing
+#[command]
+pub async fn load_post(slug: String) -> Result<Post, String> {
+    get_posts_dir()
+        .and_then(|dir| load_post_internal(&dir, &slug))
+        .map_err(|e| e.to_string())
+}
+
+fn list_posts_internal(posts_dir: &Path) -> Result<Vec<PostSummary>> {
+    let mut posts: Vec<PostSummary> = scan_posts(posts_dir)?
+        .into_iter()
+        .map(|post| PostSummary {
+            slug: post.slug,
+            title: post.metadata.title,
+            created_at: post.metadata.created_at,
+            draft: post.metadata.draft,
+        })
+        .collect();
+
+    posts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    Ok(posts)
+}
+
+/// Read and parse every `.mdx` file in the posts directory
+///
+/// Files whose frontmatter can't be parsed are skipped instead of failing the whole scan.
+pub(crate) fn scan_posts(posts_dir: &Path) -> Result<Vec<Post>> {
+    let mut posts = Vec::new();
+
+    for entry in std::fs::read_dir(posts_dir).context("Failed to read posts directory")? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("mdx") {
+            continue;
+        }
+
+        let slug = match path.file_stem() {
+            Some(stem) => stem.to_string_lossy().to_string(),
+            None => continue,
+        };
+
+        let source = std::fs::read_to_string(&path)?;
+        let (mut metadata, content) = match parse_mdx(&source) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                println!("Skipping {:?}: {:#}", path, e);
+                continue;
+            }
+        };
+        metadata.slug = Some(slug.clone());
+
+        posts.push(Post {
+            slug,
+            metadata,
+            content,
+        });
+    }
+
+    Ok(posts)
+}
+
+fn load_post_internal(posts_dir: &Path, slug: &str) -> Result<Post> {
+    if !is_valid_slug(slug) {
+        anyhow::bail!("Invalid post slug: {}", slug);
+    }
+
+    let path = posts_dir.join(format!("{}.mdx", slug));
+    let source = std::fs::read_to_string(&path)
+        .with_context(|| format!("Post not found: {}", slug))?;
+
+    let (mut metadata, content) = parse_mdx(&source)
+        .with_context(|| format!("Failed to parse {}.mdx", slug))?;
+    metadata.slug = Some(slug.to_string());
+
+    Ok(Post {
+        slug: slug.to_string(),
+        metadata,
+        content,
+    })
+}
+
+/// Split an MDX source into its parsed frontmatter and body
+pub(crate) fn parse_mdx(source: &str) -> Result<(PostMetadata, String)> {
+    let source = source.strip_prefix('\u{feff}').unwrap_or(source);
+
+    let rest = source
+        .strip_prefix("---\n")
+        .or_else(|| source.strip_prefix("---\r\n"))
+        .context("Missing frontmatter")?;
+
+    // The frontmatter ends at the first line that is exactly `---`
+    let mut offset = 0;
+    let mut end = None;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            end = Some((offset, offset + line.len()));
+            break;
+        }
+        offset += line.len();
+    }
+    let (yaml_end, body_start) = end.context("Unterminated frontmatter")?;
+
+    let metadata: PostMetadata = serde_yaml::from_str(&rest[..yaml_end])
+        .context("Invalid frontmatter")?;
+
+    // `generate_mdx` separates frontmatter and body with a blank line
+    let body = &rest[body_start..];
+    let body = body
+        .strip_prefix('\n')
+        .or_else(|| body.strip_prefix("\r\n"))
+        .unwrap_or(body);
+
+    Ok((metadata, body.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mdx() {
+        let source = "---\ntitle: Frontend Error w. Next.js App Router\ndescription: Next.js App Router에서 발생하는 에러를 처리하는 방법\ncreatedAt: 2025-02-21\ncategory: Next.js\nseries: nextjs-error-handling\nseriesOrder: 4\ntags:\n  - Next.js\n  - 에러 처리\ndraft: true\n---\n\n# Hello\n\n---\n\nBody";
+        let (metadata, content) = parse_mdx(source).unwrap();
+
+        assert_eq!(metadata.title, "Frontend Error w. Next.js App Router");
+        assert_eq!(metadata.created_at, "2025-02-21");
+        assert_eq!(metadata.category.as_deref(), Some("Next.js"));
+        assert_eq!(metadata.series.as_deref(), Some("nextjs-error-handling"));
+        assert_eq!(metadata.series_order, Some(4));
+        assert_eq!(metadata.tags, ["Next.js", "에러 처리"]);
+        assert_eq!(metadata.cover, None);
+        assert!(metadata.draft);
+        assert_eq!(content, "# Hello\n\n---\n\nBody");
+    }
+
+    #[test]
+    fn test_parse_mdx_without_frontmatter() {
+        assert!(parse_mdx("# Hello").is_err());
+        assert!(parse_mdx("---\ntitle: Open\n").is_err());
+    }
+}
@@ -0,0 +1,530 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::command;
+
+use super::github::{PostMetadata, PublishMode, SubmitError, SubmitErrorKind, SubmitPostResult};
+use super::settings::{load_workspace, Workspace};
+
+/// How often the queue is checked for posts that are due
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Submit attempts before a queued post is marked failed
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Wait before retrying a failed submit, multiplied by the number of attempts so far
+const RETRY_DELAY_MINUTES: i64 = 5;
+
+/// Serializes reads and writes of `queue.json` between the commands and the publisher
+static QUEUE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QueueStatus {
+    /// Waiting for `publish_at`, or for a retry after a failed attempt
+    Pending,
+    /// Being submitted right now
+    Publishing,
+    Published,
+    /// Gave up; `error` says why
+    Failed,
+}
+
+/// A post waiting in the publish queue, with the arguments `submit_post` is called with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedPost {
+    pub id: String,
+    #[serde(rename = "publishAt")]
+    pub publish_at: DateTime<Utc>,
+    pub metadata: PostMetadata,
+    pub content: String,
+    /// Copies of the local images kept with the queue, keyed like `submit_post`'s
+    #[serde(rename = "localImages", default)]
+    pub local_images: HashMap<String, String>,
+    #[serde(rename = "existingSlug")]
+    pub existing_slug: Option<String>,
+    #[serde(rename = "publishMode", default)]
+    pub publish_mode: PublishMode,
+    #[serde(rename = "normalizeTags", default)]
+    pub normalize_tags: bool,
+    pub status: QueueStatus,
+    /// Failed submits so far
+    #[serde(default)]
+    pub attempts: u32,
+    /// Error of the last failed submit
+    pub error: Option<String>,
+    /// When a failed submit is retried
+    #[serde(rename = "retryAt")]
+    pub retry_at: Option<DateTime<Utc>>,
+    /// Set once published
+    pub slug: Option<String>,
+    #[serde(rename = "pullRequestUrl")]
+    pub pull_request_url: Option<String>,
+    #[serde(rename = "publishedAt")]
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+impl QueuedPost {
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.status == QueueStatus::Pending
+            && self.publish_at <= now
+            && self.retry_at.is_none_or(|retry_at| retry_at <= now)
+    }
+}
+
+/// Queue a post to be submitted at `publish_at`, an RFC 3339 timestamp in the future
+///
+/// Takes the same arguments as `submit_post`. Local images are copied into the queue, as
+/// blob URLs and temporary files won't be around by then. Content with MDX errors is
+/// refused right away.
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn queue_post(
+    metadata: PostMetadata,
+    content: String,
+    local_images: HashMap<String, String>,
+    existing_slug: Option<String>,
+    publish_mode: Option<PublishMode>,
+    normalize_tags: Option<bool>,
+    publish_at: String,
+) -> Result<QueuedPost, String> {
+    let publish_at = DateTime::parse_from_rfc3339(&publish_at)
+        .map_err(|e| format!("Invalid publish time '{}': {}", publish_at, e))?
+        .with_timezone(&Utc);
+    let post = QueuedPost {
+        id: String::new(),
+        publish_at,
+        metadata,
+        content,
+        local_images,
+        existing_slug,
+        publish_mode: publish_mode.unwrap_or_default(),
+        normalize_tags: normalize_tags.unwrap_or(false),
+        status: QueueStatus::Pending,
+        attempts: 0,
+        error: None,
+        retry_at: None,
+        slug: None,
+        pull_request_url: None,
+        published_at: None,
+    };
+
+    load_workspace()
+        .and_then(|workspace| add_to_queue(&get_queue_dir()?, &workspace, post, Utc::now()))
+        .map_err(|e| e.to_string())
+}
+
+/// List queued posts by publish time, including published and failed ones
+#[command]
+pub async fn list_queued_posts() -> Result<Vec<QueuedPost>, String> {
+    get_queue_dir()
+        .and_then(|dir| load_queue(&dir))
+        .map_err(|e| e.to_string())
+}
+
+/// Take a post off the queue, or clear a published or failed one from the list
+#[command]
+pub async fn cancel_queued_post(id: String) -> Result<(), String> {
+    get_queue_dir()
+        .and_then(|dir| cancel_queued_post_internal(&dir, &id))
+        .map_err(|e| e.to_string())
+}
+
+/// Publish queued posts as they come due, for as long as the editor runs
+pub async fn run_queue() {
+    let queue_dir = match get_queue_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            println!("Publish queue disabled: {:#}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = fail_interrupted(&queue_dir) {
+        println!("Failed to read the publish queue: {:#}", e);
+    }
+
+    loop {
+        let submit = |post: QueuedPost| {
+            super::github::submit_post_internal(
+                post.metadata,
+                post.content,
+                post.local_images,
+                post.existing_slug,
+                post.publish_mode,
+                post.normalize_tags,
+            )
+        };
+        if let Err(e) = publish_due(&queue_dir, Utc::now(), submit).await {
+            println!("Failed to publish queued posts: {:#}", e);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn add_to_queue(
+    queue_dir: &Path,
+    workspace: &Workspace,
+    mut post: QueuedPost,
+    now: DateTime<Utc>,
+) -> Result<QueuedPost> {
+    if post.publish_at <= now {
+        anyhow::bail!("The publish time must be in the future; submit the post to publish it now");
+    }
+    let diagnostics = super::validate::validate_content(&post.content, &workspace.mdx_components);
+    super::validate::ensure_valid(&diagnostics)?;
+
+    // Creating the post's directory, which holds its images, reserves the id
+    std::fs::create_dir_all(queue_dir)?;
+    let base = now.format("%Y%m%d%H%M%S%3f").to_string();
+    let mut suffix = 1;
+    post.id = loop {
+        let id = if suffix == 1 { base.clone() } else { format!("{}-{}", base, suffix) };
+        match std::fs::create_dir(queue_dir.join(&id)) {
+            Ok(()) => break id,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => suffix += 1,
+            Err(e) => return Err(e.into()),
+        }
+    };
+
+    match stash_local_images(workspace, &post.content, &post.local_images, &queue_dir.join(&post.id)) {
+        Ok(local_images) => post.local_images = local_images,
+        Err(e) => {
+            remove_stash(queue_dir, &post.id);
+            return Err(e);
+        }
+    }
+
+    update_queue(queue_dir, |posts| posts.push(post.clone()))?;
+    println!("Queued '{}' for {}", post.metadata.title, post.publish_at.to_rfc3339());
+
+    Ok(post)
+}
+
+fn cancel_queued_post_internal(queue_dir: &Path, id: &str) -> Result<()> {
+    update_queue(queue_dir, |posts| {
+        let index = posts
+            .iter()
+            .position(|post| post.id == id)
+            .with_context(|| format!("No queued post '{}'", id))?;
+        if posts[index].status == QueueStatus::Publishing {
+            anyhow::bail!("'{}' is being published right now", posts[index].metadata.title);
+        }
+        posts.remove(index);
+        Ok(())
+    })??;
+
+    remove_stash(queue_dir, id);
+    Ok(())
+}
+
+/// Fail the posts a quit editor left being published
+///
+/// The submit may have pushed before the editor quit, so trying it again could publish
+/// the post twice; the user checks the blog and submits it again if needed.
+fn fail_interrupted(queue_dir: &Path) -> Result<()> {
+    update_queue(queue_dir, |posts| {
+        for post in posts.iter_mut().filter(|post| post.status == QueueStatus::Publishing) {
+            println!("Queued post '{}' was interrupted while publishing", post.metadata.title);
+            post.status = QueueStatus::Failed;
+            post.retry_at = None;
+            post.error = Some(
+                "The editor quit while publishing this post. Check whether it was published before submitting it again."
+                    .to_string(),
+            );
+        }
+    })
+}
+
+/// Submit every post that is due, one at a time, and record how each went
+///
+/// Failed submits are retried after a delay, up to `MAX_ATTEMPTS` times. Errors that
+/// another try won't fix, like invalid content or a conflict, fail the post right away.
+async fn publish_due<F, Fut>(queue_dir: &Path, now: DateTime<Utc>, submit: F) -> Result<()>
+where
+    F: Fn(QueuedPost) -> Fut,
+    Fut: Future<Output = Result<SubmitPostResult>>,
+{
+    let mut due = update_queue(queue_dir, |posts| {
+        posts
+            .iter_mut()
+            .filter(|post| post.is_due(now))
+            .map(|post| {
+                post.status = QueueStatus::Publishing;
+                post.clone()
+            })
+            .collect::<Vec<_>>()
+    })?;
+    due.sort_by_key(|post| post.publish_at);
+
+    for post in due {
+        println!("Publishing queued post '{}'", post.metadata.title);
+        let id = post.id.clone();
+        let result = submit(post).await;
+        let finished_at = Utc::now();
+
+        let published = update_queue(queue_dir, |posts| {
+            let Some(post) = posts.iter_mut().find(|post| post.id == id) else {
+                return false;
+            };
+            match result {
+                Ok(result) => {
+                    post.status = QueueStatus::Published;
+                    post.slug = Some(result.slug);
+                    post.pull_request_url = result.pull_request_url;
+                    post.published_at = Some(finished_at);
+                    post.error = None;
+                    post.retry_at = None;
+                    true
+                }
+                Err(e) => {
+                    let error = SubmitError::from(e);
+                    post.attempts += 1;
+                    let hopeless = matches!(
+                        error.kind,
+                        SubmitErrorKind::Invalid | SubmitErrorKind::RemoteChanged | SubmitErrorKind::Conflict
+                    );
+                    if hopeless || post.attempts >= MAX_ATTEMPTS {
+                        post.status = QueueStatus::Failed;
+                        post.retry_at = None;
+                    } else {
+                        post.status = QueueStatus::Pending;
+                        post.retry_at =
+                            Some(now + chrono::Duration::minutes(RETRY_DELAY_MINUTES * i64::from(post.attempts)));
+                    }
+                    println!("Queued post '{}' failed: {}", post.metadata.title, error.message);
+                    post.error = Some(error.message);
+                    false
+                }
+            }
+        })?;
+
+        if published {
+            remove_stash(queue_dir, &id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Read `queue.json` without writing it
+fn load_queue(queue_dir: &Path) -> Result<Vec<QueuedPost>> {
+    let _guard = QUEUE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    read_queue(queue_dir)
+}
+
+/// Read `queue.json`, let `update` change it and write it back
+fn update_queue<T>(queue_dir: &Path, update: impl FnOnce(&mut Vec<QueuedPost>) -> T) -> Result<T> {
+    let _guard = QUEUE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut posts = read_queue(queue_dir)?;
+
+    let result = update(&mut posts);
+    posts.sort_by_key(|post| post.publish_at);
+
+    std::fs::create_dir_all(queue_dir)?;
+    let mut temp = tempfile::NamedTempFile::new_in(queue_dir)?;
+    serde_json::to_writer_pretty(&mut temp, &posts)?;
+    temp.persist(queue_dir.join("queue.json")).map_err(|e| e.error)?;
+
+    Ok(result)
+}
+
+/// Read `queue.json`; the caller holds `QUEUE_LOCK`
+fn read_queue(queue_dir: &Path) -> Result<Vec<QueuedPost>> {
+    let path = queue_dir.join("queue.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let json = std::fs::read_to_string(&path).context("Failed to read the publish queue")?;
+    serde_json::from_str(&json).context("The publish queue file is corrupt")
+}
+
+/// Copy the local images `content` references into `dir`, keeping their file names,
+/// and map their references to the copies
+fn stash_local_images(
+    workspace: &Workspace,
+    content: &str,
+    local_images: &HashMap<String, String>,
+    dir: &Path,
+) -> Result<HashMap<String, String>> {
+    let mut stashed = HashMap::new();
+    for image_ref in crate::markdown::find_image_refs(content) {
+        if stashed.contains_key(&image_ref.url) {
+            continue;
+        }
+        let path = match super::github::resolve_local_image(workspace, &image_ref.url, local_images) {
+            Some(Ok(path)) => path,
+            Some(Err(e)) => anyhow::bail!("Could not resolve local image {}: {}", image_ref.url, e),
+            None => continue,
+        };
+
+        // One directory per image, as the file name becomes the published image's name
+        let copy_dir = dir.join(stashed.len().to_string());
+        std::fs::create_dir_all(&copy_dir)?;
+        let copy = copy_dir.join(path.file_name().unwrap_or("image".as_ref()));
+        std::fs::copy(&path, &copy).with_context(|| format!("Failed to copy local image {:?}", path))?;
+        stashed.insert(image_ref.url, copy.to_string_lossy().to_string());
+    }
+
+    Ok(stashed)
+}
+
+fn remove_stash(queue_dir: &Path, id: &str) {
+    let dir = queue_dir.join(id);
+    if dir.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            println!("Failed to remove {:?}: {}", dir, e);
+        }
+    }
+}
+
+fn get_queue_dir() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .context("Could not find config directory")?
+        .join("blog-editor")
+        .join("queue"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::settings::test_workspace;
+    use crate::commands::validate::{Diagnostic, Rule, Severity, ValidationError};
+
+    fn queued(title: &str, content: &str, publish_at: DateTime<Utc>) -> QueuedPost {
+        QueuedPost {
+            id: String::new(),
+            publish_at,
+            metadata: PostMetadata {
+                title: title.to_string(),
+                ..Default::default()
+            },
+            content: content.to_string(),
+            local_images: HashMap::new(),
+            existing_slug: None,
+            publish_mode: PublishMode::Push,
+            normalize_tags: false,
+            status: QueueStatus::Pending,
+            attempts: 0,
+            error: None,
+            retry_at: None,
+            slug: None,
+            pull_request_url: None,
+            published_at: None,
+        }
+    }
+
+    /// Stands in for `submit_post`: "Flaky" fails once, "Broken" has invalid content
+    async fn fake_submit(post: QueuedPost) -> Result<SubmitPostResult> {
+        match post.metadata.title.as_str() {
+            "Flaky" if post.attempts == 0 => anyhow::bail!("network is down"),
+            "Broken" => Err(ValidationError {
+                diagnostics: vec![Diagnostic {
+                    severity: Severity::Error,
+                    rule: Rule::BrokenLink,
+                    message: "Broken link /gone: no such post".to_string(),
+                    line: 1,
+                    column: 1,
+                }],
+            }
+            .into()),
+            title => Ok(SubmitPostResult {
+                slug: title.to_lowercase(),
+                warnings: Vec::new(),
+                pull_request_url: None,
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = test_workspace(&dir);
+        let queue_dir = workspace.root.join("queue");
+        let photo = workspace.root.join("photo.png");
+        std::fs::write(&photo, "png").unwrap();
+        let now = Utc::now();
+        let hour = chrono::Duration::hours(1);
+        let minutes = chrono::Duration::minutes;
+
+        let mut flaky = queued("Flaky", "![photo](blob:http://localhost/1)\n", now + hour);
+        flaky.local_images.insert("blob:http://localhost/1".to_string(), photo.to_string_lossy().to_string());
+        let flaky = add_to_queue(&queue_dir, &workspace, flaky, now).unwrap();
+        let stashed = PathBuf::from(&flaky.local_images["blob:http://localhost/1"]);
+        assert!(stashed.starts_with(queue_dir.join(&flaky.id)));
+        assert_eq!(stashed.file_name().unwrap(), "photo.png");
+        std::fs::remove_file(&photo).unwrap();
+
+        let broken = add_to_queue(&queue_dir, &workspace, queued("Broken", "Body", now + hour * 2), now).unwrap();
+        add_to_queue(&queue_dir, &workspace, queued("Later", "Body", now + hour * 3), now).unwrap();
+        assert!(add_to_queue(&queue_dir, &workspace, queued("Past", "Body", now - hour), now).is_err());
+        assert!(add_to_queue(&queue_dir, &workspace, queued("Invalid", "<Callout>", now + hour), now).is_err());
+
+        let status = || -> Vec<(String, QueueStatus, u32)> {
+            load_queue(&queue_dir)
+                .unwrap()
+                .into_iter()
+                .map(|post| (post.metadata.title, post.status, post.attempts))
+                .collect()
+        };
+        let pending = |title: &str, attempts| (title.to_string(), QueueStatus::Pending, attempts);
+
+        // Nothing is due yet
+        publish_due(&queue_dir, now, fake_submit).await.unwrap();
+        assert_eq!(status(), [pending("Flaky", 0), pending("Broken", 0), pending("Later", 0)]);
+
+        // The flaky post waits for a retry; the broken one fails for good
+        let later = now + hour * 2;
+        publish_due(&queue_dir, later, fake_submit).await.unwrap();
+        assert_eq!(
+            status(),
+            [
+                pending("Flaky", 1),
+                ("Broken".to_string(), QueueStatus::Failed, 1),
+                pending("Later", 0),
+            ]
+        );
+        let posts = load_queue(&queue_dir).unwrap();
+        assert_eq!(posts[0].error.as_deref(), Some("network is down"));
+        assert_eq!(posts[0].retry_at, Some(later + minutes(5)));
+        assert!(posts[1].error.as_deref().unwrap().contains("/gone"));
+
+        publish_due(&queue_dir, later + minutes(1), fake_submit).await.unwrap();
+        assert_eq!(status()[0], pending("Flaky", 1));
+
+        let before = Utc::now();
+        publish_due(&queue_dir, later + minutes(5), fake_submit).await.unwrap();
+        let posts = load_queue(&queue_dir).unwrap();
+        assert_eq!(posts[0].status, QueueStatus::Published);
+        assert_eq!(posts[0].slug.as_deref(), Some("flaky"));
+        // When the submit finished, not the time the queue was polled at
+        let published_at = posts[0].published_at.unwrap();
+        assert!(before <= published_at && published_at <= Utc::now());
+        assert!(posts[0].error.is_none());
+        assert!(!queue_dir.join(&flaky.id).exists());
+
+        // Cancelling takes a post off the queue
+        cancel_queued_post_internal(&queue_dir, &broken.id).unwrap();
+        assert!(cancel_queued_post_internal(&queue_dir, &broken.id).is_err());
+        let titles: Vec<_> = status().into_iter().map(|(title, ..)| title).collect();
+        assert_eq!(titles, ["Flaky", "Later"]);
+
+        // A post the editor quit while publishing may be out already, so it isn't retried
+        update_queue(&queue_dir, |posts| posts[1].status = QueueStatus::Publishing).unwrap();
+        fail_interrupted(&queue_dir).unwrap();
+        let posts = load_queue(&queue_dir).unwrap();
+        assert_eq!(posts[1].status, QueueStatus::Failed);
+        assert!(posts[1].error.as_deref().unwrap().contains("Check whether it was published"));
+
+        // Listing doesn't create the queue file
+        let empty = workspace.root.join("empty");
+        assert!(load_queue(&empty).unwrap().is_empty());
+        assert!(!empty.join("queue.json").exists());
+    }
+}
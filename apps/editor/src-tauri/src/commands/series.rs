@@ -0,0 +1,248 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::command;
+
+use super::github::{git_output, PostMetadata};
+use super::posts::scan_posts;
+use super::settings::{load_workspace, Workspace};
+
+/// An entry of `series.json`, matching the blog's series registry schema
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeriesInfo {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesEntry {
+    /// Key in `series.json`, used as `series` in frontmatter
+    pub id: String,
+    #[serde(flatten)]
+    pub info: SeriesInfo,
+    /// Number of posts in the series
+    #[serde(rename = "postCount")]
+    pub post_count: usize,
+}
+
+/// List the series registered in `series.json`
+#[command]
+pub async fn list_series() -> Result<Vec<SeriesEntry>, String> {
+    load_workspace()
+        .and_then(|workspace| list_series_internal(&workspace))
+        .map_err(|e| e.to_string())
+}
+
+/// Register a new series in `series.json`
+#[command]
+pub async fn create_series(id: String, info: SeriesInfo) -> Result<SeriesEntry, String> {
+    load_workspace()
+        .and_then(|workspace| create_series_internal(&workspace.series_file, &id, info))
+        .map_err(|e| e.to_string())
+}
+
+fn list_series_internal(workspace: &Workspace) -> Result<Vec<SeriesEntry>> {
+    let registry = read_registry(&workspace.series_file)?;
+    let posts = scan_posts(&workspace.posts_dir)?;
+
+    registry
+        .into_iter()
+        .map(|(id, value)| {
+            let info: SeriesInfo = serde_json::from_value(value)
+                .with_context(|| format!("Invalid series entry '{}'", id))?;
+            let post_count = posts
+                .iter()
+                .filter(|post| post.metadata.series.as_deref() == Some(id.as_str()))
+                .count();
+            Ok(SeriesEntry {
+                id,
+                info,
+                post_count,
+            })
+        })
+        .collect()
+}
+
+fn create_series_internal(series_file: &Path, id: &str, info: SeriesInfo) -> Result<SeriesEntry> {
+    let id = id.trim();
+    let valid_id = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !valid_id {
+        anyhow::bail!("Invalid series id '{}': use lowercase letters, digits and '-' only", id);
+    }
+    if info.title.trim().is_empty() {
+        anyhow::bail!("Series title is required");
+    }
+    if let Some(color) = &info.color {
+        let valid_color = color.len() == 7
+            && color.starts_with('#')
+            && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+        if !valid_color {
+            anyhow::bail!("Invalid series color '{}': use a hex color like #8B5CF6", color);
+        }
+    }
+
+    let mut registry = read_registry(series_file)?;
+    if registry.contains_key(id) {
+        anyhow::bail!("Series '{}' already exists", id);
+    }
+
+    registry.insert(id.to_string(), serde_json::to_value(&info)?);
+
+    let json = serde_json::to_string_pretty(&registry)?;
+    std::fs::write(series_file, format!("{}\n", json)).context("Failed to write series.json")?;
+
+    println!("Created series '{}' in {:?}", id, series_file);
+
+    Ok(SeriesEntry {
+        id: id.to_string(),
+        info,
+        post_count: 0,
+    })
+}
+
+/// Check the post's series against `series.json` and fill in a missing `seriesOrder`
+///
+/// The order defaults to one past the highest order among the other posts in the series.
+/// Returns whether the series was created since the last commit, in which case
+/// `series.json` has to be committed with the post.
+pub(crate) fn apply_series(
+    workspace: &Workspace,
+    metadata: &mut PostMetadata,
+    slug: &str,
+) -> Result<bool> {
+    let series = match metadata.series.as_deref().map(str::trim) {
+        Some(series) if !series.is_empty() => series.to_string(),
+        _ => {
+            metadata.series = None;
+            return Ok(false);
+        }
+    };
+
+    let registry = read_registry(&workspace.series_file)?;
+    if !registry.contains_key(&series) {
+        anyhow::bail!(
+            "Unknown series '{}'. Create it first or pick one of: {}",
+            series,
+            registry.keys().cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
+    metadata.series = Some(series.clone());
+
+    if metadata.series_order.is_none() {
+        let last_order = scan_posts(&workspace.posts_dir)?
+            .into_iter()
+            .filter(|post| post.slug != slug && post.metadata.series.as_deref() == Some(series.as_str()))
+            .filter_map(|post| post.metadata.series_order)
+            .max()
+            .unwrap_or(0);
+        metadata.series_order = Some(last_order + 1);
+    }
+
+    Ok(!committed_registry(&workspace.series_file).contains_key(&series))
+}
+
+/// `series.json` as `HEAD` has it; empty when it isn't committed
+fn committed_registry(series_file: &Path) -> serde_json::Map<String, serde_json::Value> {
+    let Some(dir) = series_file.parent() else {
+        return serde_json::Map::new();
+    };
+    let Some(name) = series_file.file_name() else {
+        return serde_json::Map::new();
+    };
+
+    git_output(dir, &["show", &format!("HEAD:./{}", name.to_string_lossy())])
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Look up a series in `series.json`
+pub(crate) fn find_series(series_file: &Path, id: &str) -> Result<Option<SeriesInfo>> {
+    read_registry(series_file)?
+        .remove(id)
+        .map(|value| {
+            serde_json::from_value(value).with_context(|| format!("Invalid series entry '{}'", id))
+        })
+        .transpose()
+}
+
+fn read_registry(series_file: &Path) -> Result<serde_json::Map<String, serde_json::Value>> {
+    if !series_file.exists() {
+        return Ok(serde_json::Map::new());
+    }
+
+    let json = std::fs::read_to_string(series_file).context("Failed to read series.json")?;
+    serde_json::from_str(&json).context("series.json is not a valid series registry")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::settings::test_workspace;
+
+    #[test]
+    fn test_series_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = test_workspace(&dir);
+        let series_file = workspace.series_file.clone();
+        std::fs::write(
+            &series_file,
+            "{\n  \"web-performance\": {\n    \"title\": \"웹 성능\",\n    \"color\": \"#F59E0B\"\n  }\n}\n",
+        )
+        .unwrap();
+        for args in [
+            &["init", "-q"][..],
+            &["-c", "user.name=Test", "-c", "user.email=test@example.com", "add", "series.json"],
+            &["-c", "user.name=Test", "-c", "user.email=test@example.com", "commit", "-q", "-m", "init"],
+        ] {
+            let output = std::process::Command::new("git").current_dir(&workspace.root).args(args).output().unwrap();
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        }
+        for (slug, order) in [("perf-1", 1), ("perf-3", 3)] {
+            std::fs::write(
+                workspace.posts_dir.join(format!("{}.mdx", slug)),
+                format!("---\ntitle: {}\ndescription: d\ncreatedAt: 2024-01-01\nseries: web-performance\nseriesOrder: {}\n---\n", slug, order),
+            )
+            .unwrap();
+        }
+
+        let info = SeriesInfo {
+            title: "Next.js 국제화".to_string(),
+            description: Some("다국어 지원".to_string()),
+            cover: None,
+            color: Some("#06B6D4".to_string()),
+        };
+        create_series_internal(&series_file, "nextjs-i18n", info.clone()).unwrap();
+        assert!(create_series_internal(&series_file, "nextjs-i18n", info.clone()).is_err());
+        assert!(create_series_internal(&series_file, "Bad Id", info.clone()).is_err());
+
+        let series = list_series_internal(&workspace).unwrap();
+        let ids: Vec<_> = series.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["web-performance", "nextjs-i18n"]);
+        assert_eq!(series[0].post_count, 2);
+        assert_eq!(series[1].info, info);
+
+        let mut metadata = PostMetadata {
+            series: Some("web-performance".to_string()),
+            ..Default::default()
+        };
+        // Only a series created since the last commit needs series.json committed
+        assert!(!apply_series(&workspace, &mut metadata, "perf-4").unwrap());
+        assert_eq!(metadata.series_order, Some(4));
+        metadata.series = Some("nextjs-i18n".to_string());
+        assert!(apply_series(&workspace, &mut metadata, "perf-4").unwrap());
+
+        metadata.series = Some("web-perfromance".to_string());
+        metadata.series_order = None;
+        let err = apply_series(&workspace, &mut metadata, "perf-4").unwrap_err().to_string();
+        assert!(err.contains("Unknown series 'web-perfromance'"));
+    }
+}
@@ -0,0 +1,357 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+/// Editor settings persisted in `<config dir>/blog-editor/settings.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Root of the blog repository; detected from the current directory when empty
+    #[serde(rename = "blogRoot")]
+    pub blog_root: String,
+    /// Posts directory, relative to the blog root unless absolute
+    #[serde(rename = "postsDir")]
+    pub posts_dir: String,
+    /// Public images directory, relative to the blog root unless absolute
+    #[serde(rename = "imagesDir")]
+    pub images_dir: String,
+    /// URL under which the images directory is served, e.g. `/images/posts`
+    #[serde(rename = "publicUrlPrefix")]
+    pub public_url_prefix: String,
+    /// Series registry, relative to the blog root unless absolute
+    #[serde(rename = "seriesFile")]
+    pub series_file: String,
+    /// Processed images wider than this are scaled down, in pixels
+    #[serde(rename = "imageMaxWidth")]
+    pub image_max_width: u32,
+    /// WebP quality of processed images, from 1 to 100
+    #[serde(rename = "imageQuality")]
+    pub image_quality: u8,
+    /// Smaller widths generated for `srcSet` alongside each processed image, in pixels
+    #[serde(rename = "imageVariantWidths")]
+    pub image_variant_widths: Vec<u32>,
+    /// Largest animated WebP or video a GIF or video upload may turn into, in bytes
+    #[serde(rename = "animationMaxBytes")]
+    pub animation_max_bytes: u64,
+    /// JSX components the blog's MDX component map provides; posts using any other
+    /// capitalized component are refused
+    #[serde(rename = "mdxComponents")]
+    pub mdx_components: Vec<String>,
+    /// GitHub REST API base URL pull requests are opened through
+    #[serde(rename = "githubApiUrl")]
+    pub github_api_url: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            blog_root: String::new(),
+            posts_dir: "apps/blog/contents/posts".to_string(),
+            images_dir: "apps/blog/public/images/posts".to_string(),
+            public_url_prefix: "/images/posts".to_string(),
+            series_file: "apps/blog/contents/series.json".to_string(),
+            image_max_width: 1200,
+            image_quality: 80,
+            image_variant_widths: vec![480, 768],
+            animation_max_bytes: 5 * 1024 * 1024,
+            mdx_components: Vec::new(),
+            github_api_url: "https://api.github.com".to_string(),
+        }
+    }
+}
+
+/// Validated, absolute locations of the blog workspace
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub root: PathBuf,
+    pub posts_dir: PathBuf,
+    pub images_dir: PathBuf,
+    /// Always starts with `/` and has no trailing `/`
+    pub public_url_prefix: String,
+    pub series_file: PathBuf,
+    pub image: ImageOptions,
+    /// Components posts may use, see [`Settings::mdx_components`]
+    pub mdx_components: Vec<String>,
+    /// Has no trailing `/`
+    pub github_api_url: String,
+}
+
+/// How uploaded images are processed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageOptions {
+    pub max_width: u32,
+    pub quality: u8,
+    /// Sorted, unique and all greater than 0
+    pub variant_widths: Vec<u32>,
+    pub max_animation_bytes: u64,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        let settings = Settings::default();
+        ImageOptions {
+            max_width: settings.image_max_width,
+            quality: settings.image_quality,
+            variant_widths: settings.image_variant_widths,
+            max_animation_bytes: settings.animation_max_bytes,
+        }
+    }
+}
+
+impl Workspace {
+    /// Public URL of a file in the images directory
+    pub fn image_url(&self, file_name: &str) -> String {
+        format!("{}/{}", self.public_url_prefix, file_name)
+    }
+
+    /// The directory the blog serves at `/`, when the images directory is the public URL
+    /// prefix inside it, as in `public/images/posts` for `/images/posts`
+    pub fn public_dir(&self) -> Option<PathBuf> {
+        let mut dir = self.images_dir.as_path();
+        for part in self.public_url_prefix.rsplit('/').filter(|part| !part.is_empty()) {
+            if dir.file_name()? != std::ffi::OsStr::new(part) {
+                return None;
+            }
+            dir = dir.parent()?;
+        }
+        Some(dir.to_path_buf())
+    }
+
+    /// The file in the images directory a public URL points to, if any
+    pub fn image_file(&self, url: &str) -> Option<PathBuf> {
+        let name = url
+            .strip_prefix(&self.public_url_prefix)?
+            .strip_prefix('/')?;
+        if name.is_empty() || name.split('/').any(|part| part == "..") {
+            return None;
+        }
+        Some(self.images_dir.join(name))
+    }
+}
+
+impl Settings {
+    /// Resolve the configured paths and check that they form a valid blog workspace
+    pub fn resolve(&self) -> Result<Workspace> {
+        let root = if self.blog_root.trim().is_empty() {
+            detect_blog_root()?
+        } else {
+            PathBuf::from(self.blog_root.trim())
+        };
+
+        if !root.is_dir() {
+            anyhow::bail!("Blog root {:?} does not exist", root);
+        }
+        if !root.join(".git").exists() {
+            anyhow::bail!("Blog root {:?} is not a git repository", root);
+        }
+
+        let posts_dir = root.join(&self.posts_dir);
+        if !posts_dir.is_dir() {
+            anyhow::bail!("Posts directory {:?} does not exist", posts_dir);
+        }
+
+        let images_dir = root.join(&self.images_dir);
+        if !images_dir.is_dir() {
+            anyhow::bail!("Images directory {:?} does not exist", images_dir);
+        }
+
+        let prefix = self.public_url_prefix.trim().trim_end_matches('/');
+        if !prefix.starts_with('/') {
+            anyhow::bail!("Public URL prefix must start with '/': {}", self.public_url_prefix);
+        }
+
+        // The series registry may not exist until the first series is created
+        let series_file = root.join(&self.series_file);
+        if !series_file.parent().is_some_and(Path::is_dir) {
+            anyhow::bail!("Series file {:?} is not in an existing directory", series_file);
+        }
+
+        if self.image_max_width == 0 {
+            anyhow::bail!("Image max width must be greater than 0");
+        }
+        if !(1..=100).contains(&self.image_quality) {
+            anyhow::bail!("Image quality must be between 1 and 100: {}", self.image_quality);
+        }
+        if self.image_variant_widths.contains(&0) {
+            anyhow::bail!("Image variant widths must be greater than 0");
+        }
+        if self.animation_max_bytes == 0 {
+            anyhow::bail!("Animation size limit must be greater than 0");
+        }
+        let github_api_url = self.github_api_url.trim().trim_end_matches('/');
+        if !github_api_url.starts_with("https://") && !github_api_url.starts_with("http://") {
+            anyhow::bail!("GitHub API URL must start with http:// or https://: {}", self.github_api_url);
+        }
+        let mut variant_widths = self.image_variant_widths.clone();
+        variant_widths.sort_unstable();
+        variant_widths.dedup();
+
+        Ok(Workspace {
+            root,
+            posts_dir,
+            images_dir,
+            public_url_prefix: prefix.to_string(),
+            series_file,
+            image: ImageOptions {
+                max_width: self.image_max_width,
+                quality: self.image_quality,
+                variant_widths,
+                max_animation_bytes: self.animation_max_bytes,
+            },
+            mdx_components: self
+                .mdx_components
+                .iter()
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            github_api_url: github_api_url.to_string(),
+        })
+    }
+}
+
+/// Get the current settings
+#[command]
+pub fn get_settings() -> Result<Settings, String> {
+    get_settings_path()
+        .and_then(|path| load_settings(&path))
+        .map_err(|e| e.to_string())
+}
+
+/// Validate and save new settings
+#[command]
+pub fn set_settings(settings: Settings) -> Result<Settings, String> {
+    get_settings_path()
+        .and_then(|path| set_settings_internal(&path, settings))
+        .map_err(|e| e.to_string())
+}
+
+/// Load the settings and resolve the blog workspace they describe
+pub(crate) fn load_workspace() -> Result<Workspace> {
+    load_settings(&get_settings_path()?)?
+        .resolve()
+        .context("Blog workspace is not configured correctly; check the editor settings")
+}
+
+fn set_settings_internal(path: &Path, settings: Settings) -> Result<Settings> {
+    settings.resolve()?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let json = serde_json::to_string_pretty(&settings)?;
+    std::fs::write(path, json).context("Failed to write settings file")?;
+
+    println!("Settings saved to: {:?}", path);
+
+    Ok(settings)
+}
+
+fn load_settings(path: &Path) -> Result<Settings> {
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+
+    let json = std::fs::read_to_string(path).context("Failed to read settings file")?;
+    serde_json::from_str(&json).context("Settings file is corrupt")
+}
+
+fn get_settings_path() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .context("Could not find config directory")?
+        .join("blog-editor")
+        .join("settings.json"))
+}
+
+/// Look for the monorepo root (has pnpm-workspace.yaml) from the current directory
+fn detect_blog_root() -> Result<PathBuf> {
+    let current_dir = std::env::current_dir()?;
+
+    current_dir
+        .ancestors()
+        .take(3)
+        .find(|path| path.join("pnpm-workspace.yaml").exists())
+        .map(Path::to_path_buf)
+        .context("Could not find blog project root; set it in the editor settings")
+}
+
+/// A workspace in a temporary directory, with the posts and images directories created
+#[cfg(test)]
+pub(crate) fn test_workspace(dir: &tempfile::TempDir) -> Workspace {
+    let root = dir.path().to_path_buf();
+    let workspace = Workspace {
+        posts_dir: root.join("posts"),
+        images_dir: root.join("public/images/posts"),
+        public_url_prefix: "/images/posts".to_string(),
+        series_file: root.join("series.json"),
+        image: ImageOptions::default(),
+        mdx_components: Vec::new(),
+        github_api_url: "https://api.github.com".to_string(),
+        root,
+    };
+    std::fs::create_dir_all(&workspace.posts_dir).unwrap();
+    std::fs::create_dir_all(&workspace.images_dir).unwrap();
+    workspace
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_resolve_and_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("contents/posts")).unwrap();
+
+        let mut settings = Settings {
+            blog_root: root.to_string_lossy().to_string(),
+            posts_dir: "contents/posts".to_string(),
+            images_dir: "public/images/posts".to_string(),
+            public_url_prefix: "/images/posts/".to_string(),
+            series_file: "contents/series.json".to_string(),
+            ..Default::default()
+        };
+        let settings_path = root.join("config/settings.json");
+
+        // Not a git repository yet, and the images directory is missing
+        assert!(set_settings_internal(&settings_path, settings.clone()).is_err());
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        let err = settings.resolve().unwrap_err().to_string();
+        assert!(err.contains("Images directory"));
+        assert!(!root.join("public/images/posts").exists());
+
+        std::fs::create_dir_all(root.join("public/images/posts")).unwrap();
+        set_settings_internal(&settings_path, settings.clone()).unwrap();
+        assert_eq!(load_settings(&settings_path).unwrap(), settings);
+
+        let workspace = settings.resolve().unwrap();
+        assert_eq!(workspace.image_url("a.webp"), "/images/posts/a.webp");
+        assert_eq!(
+            workspace.image_file("/images/posts/a.webp"),
+            Some(root.join("public/images/posts").join("a.webp"))
+        );
+        assert_eq!(workspace.image_file("/images/other/a.webp"), None);
+        assert_eq!(workspace.image_file("/images/posts/../a.webp"), None);
+
+        assert_eq!(workspace.image, ImageOptions::default());
+
+        settings.image_quality = 0;
+        assert!(settings.resolve().is_err());
+        settings.image_quality = 80;
+        settings.image_variant_widths = vec![768, 0];
+        assert!(settings.resolve().is_err());
+        settings.image_variant_widths = vec![768, 480, 768];
+        assert_eq!(settings.resolve().unwrap().image.variant_widths, [480, 768]);
+
+        settings.github_api_url = "https://github.example.com/api/v3/".to_string();
+        assert_eq!(settings.resolve().unwrap().github_api_url, "https://github.example.com/api/v3");
+        settings.github_api_url = "github.example.com".to_string();
+        assert!(settings.resolve().is_err());
+        settings.github_api_url = "https://api.github.com".to_string();
+
+        settings.public_url_prefix = "images".to_string();
+        assert!(settings.resolve().is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use markdown::mdast::{AttributeContent, AttributeValue, Node};
+use markdown::message::Place;
+use markdown::unist::Position;
+use markdown::{Constructs, MdxSignal, ParseOptions};
+use serde::Serialize;
+use tauri::command;
+
+use super::settings::load_workspace;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// The blog build would fail; blocks submitting
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Rule {
+    /// Content that doesn't parse as MDX, e.g. an unclosed JSX element or `{`
+    Syntax,
+    /// A capitalized JSX component missing from the `mdxComponents` setting
+    UnknownComponent,
+    /// `import` or `export`, which the blog's MDX loader removes
+    Esm,
+    EmptyHeading,
+    MissingAlt,
+    /// A fenced code block without a language
+    CodeLanguage,
+    /// A link to a post or file that doesn't exist, see `check_links`
+    BrokenLink,
+}
+
+/// A problem found in post content
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub rule: Rule,
+    pub message: String,
+    /// 1-based line in the content, without frontmatter
+    pub line: usize,
+    /// 1-based column
+    pub column: usize,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Returned when submitting content that has errors
+#[derive(Debug, thiserror::Error)]
+#[error("The post has errors: {}", .diagnostics.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+pub struct ValidationError {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Check post content for MDX the blog can't build and for common authoring mistakes
+#[command]
+pub async fn validate_post(content: String) -> Result<Vec<Diagnostic>, String> {
+    load_workspace()
+        .map(|workspace| validate_content(&content, &workspace.mdx_components))
+        .map_err(|e| e.to_string())
+}
+
+/// Parse `content` as MDX and report problems, sorted by position
+///
+/// `components` are the JSX components the blog provides. Lowercase elements such as
+/// `<img>` or `<aside>` are HTML and always allowed. When the content doesn't parse, the
+/// parse error is the only diagnostic.
+pub(crate) fn validate_content(content: &str, components: &[String]) -> Vec<Diagnostic> {
+    let tree = match markdown::to_mdast(content, &parse_options()) {
+        Ok(tree) => tree,
+        Err(message) => {
+            let (line, column) = match message.place.as_deref() {
+                Some(Place::Point(point)) => (point.line, point.column),
+                Some(Place::Position(position)) => (position.start.line, position.start.column),
+                None => (1, 1),
+            };
+            return vec![Diagnostic {
+                severity: Severity::Error,
+                rule: Rule::Syntax,
+                message: message.reason,
+                line,
+                column,
+            }];
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    check_node(&tree, components, &mut diagnostics);
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    diagnostics
+}
+
+/// Fail with the errors among `diagnostics`, if there are any
+pub(crate) fn ensure_valid(diagnostics: &[Diagnostic]) -> Result<(), ValidationError> {
+    let errors: Vec<_> = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .cloned()
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError { diagnostics: errors })
+    }
+}
+
+/// MDX plus the GFM extensions the blog enables with `remark-gfm`
+pub(crate) fn parse_options() -> ParseOptions {
+    ParseOptions {
+        constructs: Constructs {
+            gfm_autolink_literal: true,
+            gfm_footnote_definition: true,
+            gfm_label_start_footnote: true,
+            gfm_strikethrough: true,
+            gfm_table: true,
+            gfm_task_list_item: true,
+            ..Constructs::mdx()
+        },
+        // Without a parser `import` and `export` lines are read as paragraphs. Their
+        // JavaScript isn't checked; they are only reported.
+        mdx_esm_parse: Some(Box::new(|_| MdxSignal::Ok)),
+        ..ParseOptions::mdx()
+    }
+}
+
+fn check_node(node: &Node, components: &[String], diagnostics: &mut Vec<Diagnostic>) {
+    let mut report = |severity, rule, message: String, position: Option<&Position>| {
+        let (line, column) = position.map_or((1, 1), |p| (p.start.line, p.start.column));
+        diagnostics.push(Diagnostic {
+            severity,
+            rule,
+            message,
+            line,
+            column,
+        });
+    };
+
+    match node {
+        Node::MdxJsxFlowElement(element) => check_jsx(
+            element.name.as_deref(),
+            &element.attributes,
+            element.position.as_ref(),
+            components,
+            &mut report,
+        ),
+        Node::MdxJsxTextElement(element) => check_jsx(
+            element.name.as_deref(),
+            &element.attributes,
+            element.position.as_ref(),
+            components,
+            &mut report,
+        ),
+        Node::MdxjsEsm(esm) => report(
+            Severity::Warning,
+            Rule::Esm,
+            "Imports and exports are removed by the blog's MDX loader".to_string(),
+            esm.position.as_ref(),
+        ),
+        Node::Heading(heading) if node.to_string().trim().is_empty() => report(
+            Severity::Warning,
+            Rule::EmptyHeading,
+            format!("Heading level {} is empty", heading.depth),
+            heading.position.as_ref(),
+        ),
+        Node::Image(image) if image.alt.trim().is_empty() => report(
+            Severity::Warning,
+            Rule::MissingAlt,
+            format!("Image {} has no alt text", image.url),
+            image.position.as_ref(),
+        ),
+        Node::ImageReference(image) if image.alt.trim().is_empty() => report(
+            Severity::Warning,
+            Rule::MissingAlt,
+            format!("Image [{}] has no alt text", image.identifier),
+            image.position.as_ref(),
+        ),
+        Node::Code(code) if code.lang.is_none() => report(
+            Severity::Warning,
+            Rule::CodeLanguage,
+            "Code block has no language".to_string(),
+            code.position.as_ref(),
+        ),
+        _ => {}
+    }
+
+    for child in node.children().into_iter().flatten() {
+        check_node(child, components, diagnostics);
+    }
+}
+
+fn check_jsx(
+    name: Option<&str>,
+    attributes: &[AttributeContent],
+    position: Option<&Position>,
+    components: &[String],
+    report: &mut impl FnMut(Severity, Rule, String, Option<&Position>),
+) {
+    // Fragments have no name
+    let Some(name) = name else {
+        return;
+    };
+
+    // `<Tabs.Item>` is allowed when either `Tabs.Item` or `Tabs` is
+    let root = name.split('.').next().unwrap_or(name);
+    let is_html = !name.contains('.') && name.starts_with(|c: char| c.is_ascii_lowercase());
+    if !is_html && !components.iter().any(|c| c == name || c == root) {
+        report(
+            Severity::Error,
+            Rule::UnknownComponent,
+            format!("Unknown component <{}>", name),
+            position,
+        );
+    }
+
+    if matches!(name, "img" | "Image") {
+        let has_alt = attributes.iter().any(|attribute| match attribute {
+            AttributeContent::Property(property) if property.name == "alt" => match &property.value {
+                Some(AttributeValue::Literal(alt)) => !alt.trim().is_empty(),
+                Some(AttributeValue::Expression(_)) => true,
+                None => false,
+            },
+            // `{...props}` may well contain one
+            AttributeContent::Expression(_) => true,
+            _ => false,
+        });
+        if !has_alt {
+            report(
+                Severity::Warning,
+                Rule::MissingAlt,
+                format!("<{}> has no alt text", name),
+                position,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(content: &str, components: &[&str]) -> Vec<(Rule, usize, usize)> {
+        let components: Vec<String> = components.iter().map(|c| c.to_string()).collect();
+        validate_content(content, &components)
+            .into_iter()
+            .map(|d| (d.rule, d.line, d.column))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_content() {
+        let content = "# Title\n\n\
+                       ## \n\n\
+                       ![](/images/posts/a.webp) and ![ok](/images/posts/b.webp)\n\n\
+                       <img src=\"/images/posts/c.webp\" alt=\"\" />\n\n\
+                       ```\nplain\n```\n\n\
+                       ```ts\nconst a = 1 < 2;\n```\n\n\
+                       <aside>Note with <Callout /> and <Tabs.Item>x</Tabs.Item></aside>\n\n\
+                       a < b, <>fragment</> and <img alt={title} src=\"x\" />\n";
+
+        assert_eq!(
+            rules(content, &["Tabs"]),
+            [
+                (Rule::EmptyHeading, 3, 1),
+                (Rule::MissingAlt, 5, 1),
+                (Rule::MissingAlt, 7, 1),
+                (Rule::CodeLanguage, 9, 1),
+                (Rule::UnknownComponent, 17, 18),
+            ]
+        );
+
+        let diagnostics = validate_content(content, &[]);
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.message == "Unknown component <Tabs.Item>" && d.severity == Severity::Error));
+        assert_eq!(ensure_valid(&diagnostics).unwrap_err().diagnostics.len(), 2);
+        assert!(ensure_valid(&validate_content(content, &["Callout".to_string(), "Tabs".to_string()])).is_ok());
+    }
+
+    #[test]
+    fn test_validate_content_syntax_errors() {
+        let diagnostics = validate_content("# Title\n\n<Callout>\ntext\n", &["Callout".to_string()]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].severity, diagnostics[0].rule), (Severity::Error, Rule::Syntax));
+        assert!(diagnostics[0].message.contains("Expected a closing tag for `<Callout>` (3:1)"));
+
+        assert_eq!(rules("<div>\n\n</span>\n", &[]), [(Rule::Syntax, 3, 1)]);
+        assert_eq!(rules("text {unclosed\n", &[]), [(Rule::Syntax, 1, 15)]);
+        assert_eq!(rules("import X from 'x'\n\n<X />\n", &["X"]), [(Rule::Esm, 1, 1)]);
+
+        let error = ensure_valid(&validate_content("<div>\n", &[])).unwrap_err();
+        assert!(error.to_string().starts_with("The post has errors: 2:1: Expected a closing tag for `<div>` (1:1)"));
+    }
+}
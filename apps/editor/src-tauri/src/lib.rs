@@ -1,4 +1,6 @@
 mod commands;
+mod markdown;
+mod slug;
 
 use tauri::Manager;
 
@@ -15,6 +17,9 @@ pub fn run() {
             // Get the main window and set up any initial state
             let _main_window = app.get_webview_window("main").unwrap();
             
+            // Publish queued posts when they come due
+            tauri::async_runtime::spawn(commands::queue::run_queue());
+            
             // Log startup
             println!("Blog Editor started successfully!");
             
@@ -22,9 +27,34 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::image::process_image,
+            commands::image::inspect_image_metadata,
+            commands::orphans::find_orphaned_images,
+            commands::orphans::delete_orphaned_images,
+            commands::cover::generate_cover,
+            commands::validate::validate_post,
+            commands::links::check_links,
             commands::github::submit_post,
+            commands::github::preview_submit,
+            commands::github::unpublish_post,
+            commands::github::delete_post,
+            commands::queue::queue_post,
+            commands::queue::list_queued_posts,
+            commands::queue::cancel_queued_post,
             commands::github::save_draft,
+            commands::github::list_drafts,
+            commands::github::load_draft,
+            commands::github::delete_draft,
             commands::github::get_blog_path,
+            commands::posts::list_posts,
+            commands::posts::load_post,
+            commands::history::list_post_revisions,
+            commands::history::load_post_revision,
+            commands::history::restore_post_revision,
+            commands::series::list_series,
+            commands::series::create_series,
+            commands::catalog::get_catalog,
+            commands::settings::get_settings,
+            commands::settings::set_settings,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
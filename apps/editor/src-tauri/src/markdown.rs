@@ -0,0 +1,215 @@
+//! Helpers for finding references inside post content
+
+use markdown::mdast::Node;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+/// `![alt](url "title")` and `![alt](<url with spaces>)`; the alt text may contain a link
+static MARKDOWN_IMAGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"!\[(?:[^\[\]]|\[[^\]]*\])*\]\(\s*(?:<([^>]*)>|([^)\s]+))"#).unwrap()
+});
+
+/// `<Image src="...">`, `<img src='...'>`, `<video src="...">` and `<Image src={"..."}>`
+static JSX_IMAGE_SRC: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<(?:Image|img|video|source)\b[^>]*?\bsrc\s*=\s*(?:"([^"]*)"|'([^']*)'|\{\s*["'`]([^"'`]*)["'`]\s*\})"#)
+        .unwrap()
+});
+
+/// `<img srcSet="/a-480w.webp 480w, /a.webp 1200w">`, with the same quoting as `src`
+static JSX_IMAGE_SRCSET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<(?:Image|img)\b[^>]*?\bsrcSet\s*=\s*(?:"([^"]*)"|'([^']*)'|\{\s*["'`]([^"'`]*)["'`]\s*\})"#)
+        .unwrap()
+});
+
+/// `<video poster="...">`, with the same quoting as `src`
+static JSX_VIDEO_POSTER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<video\b[^>]*?\bposter\s*=\s*(?:"([^"]*)"|'([^']*)'|\{\s*["'`]([^"'`]*)["'`]\s*\})"#)
+        .unwrap()
+});
+
+/// An image reference found in post content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageRef {
+    pub url: String,
+    /// Byte range of `url` in the content
+    pub range: Range<usize>,
+}
+
+/// Find every image reference in markdown image syntax, in `<Image>`/`<img>` `src` and
+/// `srcSet` attributes and in `<video>` `src` and `poster` attributes
+///
+/// References inside code blocks and inline code are examples, not images, and are skipped.
+pub fn find_image_refs(content: &str) -> Vec<ImageRef> {
+    let mut refs: Vec<ImageRef> = MARKDOWN_IMAGE
+        .captures_iter(content)
+        .chain(JSX_IMAGE_SRC.captures_iter(content))
+        .chain(JSX_VIDEO_POSTER.captures_iter(content))
+        .filter_map(|caps| caps.iter().skip(1).flatten().next())
+        .map(|m| ImageRef {
+            url: m.as_str().to_string(),
+            range: m.range(),
+        })
+        .collect();
+
+    // Each srcSet candidate is a URL followed by an optional width or density descriptor
+    for m in JSX_IMAGE_SRCSET
+        .captures_iter(content)
+        .filter_map(|caps| caps.iter().skip(1).flatten().next())
+    {
+        let mut offset = m.start();
+        for candidate in m.as_str().split(',') {
+            let trimmed = candidate.trim_start();
+            let start = offset + candidate.len() - trimmed.len();
+            let url = trimmed.split_whitespace().next().unwrap_or_default();
+            if !url.is_empty() {
+                refs.push(ImageRef {
+                    url: url.to_string(),
+                    range: start..start + url.len(),
+                });
+            }
+            offset += candidate.len() + 1;
+        }
+    }
+
+    let code = code_ranges(content);
+    refs.retain(|r| !code.iter().any(|code| code.start <= r.range.start && r.range.end <= code.end));
+
+    refs.sort_by_key(|r| r.range.start);
+    refs
+}
+
+/// Byte ranges of code blocks and inline code, found by parsing `content` as MDX
+///
+/// Content that doesn't parse has no ranges; submitting refuses it anyway.
+fn code_ranges(content: &str) -> Vec<Range<usize>> {
+    let Ok(tree) = markdown::to_mdast(content, &crate::commands::validate::parse_options()) else {
+        return Vec::new();
+    };
+
+    let mut ranges = Vec::new();
+    collect_code_ranges(&tree, &mut ranges);
+    ranges
+}
+
+fn collect_code_ranges(node: &Node, ranges: &mut Vec<Range<usize>>) {
+    if let Node::Code(_) | Node::InlineCode(_) = node {
+        if let Some(position) = node.position() {
+            ranges.push(position.start.offset..position.end.offset);
+        }
+        return;
+    }
+
+    for child in node.children().into_iter().flatten() {
+        collect_code_ranges(child, ranges);
+    }
+}
+
+/// Every URL under `prefix` mentioned anywhere in `content`, in whatever syntax: image
+/// references, plain links, `srcSet` candidates or frontmatter such as `cover`
+pub fn find_urls_with_prefix(content: &str, prefix: &str) -> Vec<String> {
+    let prefix = prefix.trim_end_matches('/');
+    let pattern = Regex::new(&format!(r#"{}/[^\s"'`<>()\[\]{{}},]+"#, regex::escape(prefix)))
+        .expect("escaped prefix is a valid pattern");
+
+    pattern
+        .find_iter(content)
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+/// Replace the given ranges of `content`, which must not overlap
+pub fn replace_ranges(content: &str, replacements: &[(Range<usize>, String)]) -> String {
+    let mut sorted: Vec<_> = replacements.iter().collect();
+    sorted.sort_by_key(|(range, _)| range.start);
+
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for (range, replacement) in sorted {
+        result.push_str(&content[last..range.start]);
+        result.push_str(replacement);
+        last = range.end;
+    }
+    result.push_str(&content[last..]);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_image_refs() {
+        let content = r#"![shot](blob:http://localhost/123) text ![a b](<file:///tmp/a b.png> "title")
+<Image src="/images/posts/a.webp" alt="a" />
+<img alt="x" src='file:///tmp/x.png' />
+<Image src={"/images/posts/b.webp"} />
+<img src="/images/posts/c.webp" srcSet="/images/posts/c-480w.webp 480w,/images/posts/c.webp 1200w" />
+![[caption](https://example.com)](/images/posts/d.png)
+<video src="/images/posts/e.mp4" poster="/images/posts/e-poster.webp" autoPlay loop muted />
+[not an image](/posts/other)
+
+```mdx
+![example](./example.png)
+<img src="example.png" />
+```
+
+한국어 `![inline](inline.png)` 다음 ![f](/images/posts/f.png)"#;
+
+        let urls: Vec<_> = find_image_refs(content).into_iter().map(|r| r.url).collect();
+        assert_eq!(
+            urls,
+            [
+                "blob:http://localhost/123",
+                "file:///tmp/a b.png",
+                "/images/posts/a.webp",
+                "file:///tmp/x.png",
+                "/images/posts/b.webp",
+                "/images/posts/c.webp",
+                "/images/posts/c-480w.webp",
+                "/images/posts/c.webp",
+                "/images/posts/d.png",
+                "/images/posts/e.mp4",
+                "/images/posts/e-poster.webp",
+                "/images/posts/f.png",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_urls_with_prefix() {
+        let content = "---\ncover: /images/posts/cover.webp\n---\n[video](/images/posts/a.mp4), \
+                       <img srcSet=\"/images/posts/b-480w.webp 480w,/images/posts/b.webp 1200w\" />\n\
+                       /images/postscript.png";
+
+        assert_eq!(
+            find_urls_with_prefix(content, "/images/posts/"),
+            [
+                "/images/posts/cover.webp",
+                "/images/posts/a.mp4",
+                "/images/posts/b-480w.webp",
+                "/images/posts/b.webp",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replace_ranges() {
+        let content = "![a](one) ![b](two)";
+        let refs = find_image_refs(content);
+        let replacements: Vec<_> = refs
+            .iter()
+            .map(|r| (r.range.clone(), r.url.to_uppercase()))
+            .collect();
+
+        assert_eq!(replace_ranges(content, &replacements), "![a](ONE) ![b](TWO)");
+
+        let content = r#"<img srcSet="one 480w,  two 2x" />"#;
+        let replacements: Vec<_> = find_image_refs(content)
+            .iter()
+            .map(|r| (r.range.clone(), r.url.to_uppercase()))
+            .collect();
+        assert_eq!(replace_ranges(content, &replacements), r#"<img srcSet="ONE 480w,  TWO 2x" />"#);
+    }
+}
@@ -0,0 +1,52 @@
+//! Revised Romanization of Korean for building ASCII slugs
+
+const HANGUL_START: u32 = 0xAC00;
+const HANGUL_END: u32 = 0xD7A3;
+
+const INITIALS: [&str; 19] = [
+    "g", "kk", "n", "d", "tt", "r", "m", "b", "pp", "s", "ss", "", "j", "jj", "ch", "k", "t", "p",
+    "h",
+];
+
+const MEDIALS: [&str; 21] = [
+    "a", "ae", "ya", "yae", "eo", "e", "yeo", "ye", "o", "wa", "wae", "oe", "yo", "u", "wo", "we",
+    "wi", "yu", "eu", "ui", "i",
+];
+
+const FINALS: [&str; 28] = [
+    "", "k", "k", "k", "n", "n", "n", "t", "l", "k", "m", "l", "l", "l", "p", "l", "m", "p", "p",
+    "t", "t", "ng", "t", "t", "k", "t", "p", "t",
+];
+
+/// Romanize a single precomposed Hangul syllable, e.g. `한` -> `han`
+///
+/// Returns `None` for anything that isn't a Hangul syllable. Each syllable is
+/// transliterated on its own, without the sound-change rules between syllables.
+pub fn romanize_syllable(c: char) -> Option<String> {
+    let code = c as u32;
+    if !(HANGUL_START..=HANGUL_END).contains(&code) {
+        return None;
+    }
+
+    let index = (code - HANGUL_START) as usize;
+    let initial = index / (21 * 28);
+    let medial = (index % (21 * 28)) / 28;
+    let last = index % 28;
+
+    Some(format!("{}{}{}", INITIALS[initial], MEDIALS[medial], FINALS[last]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_romanize_syllable() {
+        let romanize = |s: &str| s.chars().filter_map(romanize_syllable).collect::<String>();
+
+        assert_eq!(romanize("한글"), "hangeul");
+        assert_eq!(romanize("제목"), "jemok");
+        assert_eq!(romanize("에러처리"), "ereocheori");
+        assert_eq!(romanize_syllable('a'), None);
+    }
+}
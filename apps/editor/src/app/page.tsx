@@ -5,6 +5,7 @@ import { MetadataForm } from "@/components/editor/MetadataForm";
 import { MarkdownEditor } from "@/components/editor/MarkdownEditor";
 import { Preview } from "@/components/editor/Preview";
 import { Toolbar } from "@/components/editor/Toolbar";
+import { submitPost, type PublishMode } from "@/lib/tauri";
 import type { PostMeta } from "@hyoungmin/schema";
 
 type ViewMode = "split" | "editor" | "preview";
@@ -22,6 +23,10 @@ export default function EditorPage() {
   const [content, setContent] = useState("");
   const [viewMode, setViewMode] = useState<ViewMode>("split");
   const [isSubmitting, setIsSubmitting] = useState(false);
+  // Slug of the published post being edited; submitting again updates it
+  const [existingSlug, setExistingSlug] = useState<string>();
+  const [publishMode, setPublishMode] = useState<PublishMode>("push");
+  const [normalizeTags, setNormalizeTags] = useState(false);
   const [localImages, setLocalImages] = useState<Map<string, string>>(
     new Map(),
   );
@@ -36,26 +41,43 @@ export default function EditorPage() {
   const handleSubmit = useCallback(async () => {
     setIsSubmitting(true);
     try {
-      // Import Tauri APIs dynamically (only available in Tauri context)
-      const { invoke } = await import("@tauri-apps/api/core");
-
-      await invoke("submit_post", {
-        metadata: {
+      const result = await submitPost(
+        {
           ...metadata,
           createdAt: metadata.createdAt.toISOString().split("T")[0],
         },
         content,
-        localImages: Object.fromEntries(localImages),
-      });
+        Object.fromEntries(localImages),
+        existingSlug,
+        publishMode,
+        normalizeTags,
+      );
+      setExistingSlug(result.slug);
 
-      alert("포스트가 성공적으로 제출되었습니다!");
+      const details = [...result.warnings];
+      if (result.pullRequestUrl) {
+        details.push(`Pull Request: ${result.pullRequestUrl}`);
+      }
+      alert(["포스트가 성공적으로 제출되었습니다!", ...details].join("\n"));
     } catch (error) {
       console.error("Submit error:", error);
-      alert(`제출 실패: ${error}`);
+      // submit_post rejects with { kind, message, files, retryable }
+      const message =
+        typeof error === "object" && error !== null && "message" in error
+          ? error.message
+          : error;
+      alert(`제출 실패: ${message}`);
     } finally {
       setIsSubmitting(false);
     }
-  }, [metadata, content, localImages]);
+  }, [
+    metadata,
+    content,
+    localImages,
+    existingSlug,
+    publishMode,
+    normalizeTags,
+  ]);
 
   const handleSaveDraft = useCallback(async () => {
     try {
@@ -87,6 +109,10 @@ export default function EditorPage() {
             onViewModeChange={setViewMode}
             onSave={handleSaveDraft}
             onSubmit={handleSubmit}
+            publishMode={publishMode}
+            onPublishModeChange={setPublishMode}
+            normalizeTags={normalizeTags}
+            onNormalizeTagsChange={setNormalizeTags}
             isSubmitting={isSubmitting}
             isValid={!!metadata.title && !!metadata.description && !!content}
           />
@@ -6,6 +6,7 @@ import Editor, {
   type Monaco,
   type EditorProps,
 } from "@monaco-editor/react";
+import { imageToMdx, type ProcessImageResult } from "@/lib/tauri";
 
 type IStandaloneCodeEditor = Parameters<OnMount>[0];
 
@@ -101,7 +102,8 @@ export function MarkdownEditor({
       if (!files?.length) return;
 
       for (const file of Array.from(files)) {
-        if (!file.type.startsWith("image/")) continue;
+        if (!file.type.startsWith("image/") && !file.type.startsWith("video/"))
+          continue;
 
         try {
           // In Tauri context, we'll process the image
@@ -112,7 +114,7 @@ export function MarkdownEditor({
           const uint8Array = new Uint8Array(buffer);
 
           // Process image through Tauri
-          const result = await invoke<{ path: string; localPath: string }>(
+          const result = await invoke<ProcessImageResult>(
             "process_image",
             {
               fileName: file.name,
@@ -125,7 +127,7 @@ export function MarkdownEditor({
           if (editor) {
             const position = editor.getPosition();
             if (position) {
-              const imageMarkdown = `![${file.name.replace(/\.[^/.]+$/, "")}](${result.path})\n`;
+              const imageMarkdown = `${imageToMdx(result, file.name.replace(/\.[^/.]+$/, ""))}\n`;
               editor.executeEdits("insert-image", [
                 {
                   range: {
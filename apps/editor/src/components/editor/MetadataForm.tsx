@@ -2,6 +2,7 @@
 
 import { useState, useCallback, type KeyboardEvent } from "react";
 import type { PostMeta } from "@hyoungmin/schema";
+import { generateCover } from "@/lib/tauri";
 
 interface MetadataFormProps {
   metadata: PostMeta;
@@ -10,6 +11,8 @@ interface MetadataFormProps {
 
 export function MetadataForm({ metadata, onChange }: MetadataFormProps) {
   const [tagInput, setTagInput] = useState("");
+  const [coverError, setCoverError] = useState<string | null>(null);
+  const [isGeneratingCover, setIsGeneratingCover] = useState(false);
 
   const handleChange = useCallback(
     <K extends keyof PostMeta>(field: K, value: PostMeta[K]) => {
@@ -52,6 +55,22 @@ export function MetadataForm({ metadata, onChange }: MetadataFormProps) {
     [handleAddTag, handleRemoveTag, tagInput, metadata.tags],
   );
 
+  const handleGenerateCover = useCallback(async () => {
+    setIsGeneratingCover(true);
+    setCoverError(null);
+    try {
+      const cover = await generateCover({
+        ...metadata,
+        createdAt: metadata.createdAt.toISOString().split("T")[0],
+      });
+      handleChange("cover", cover);
+    } catch (error) {
+      setCoverError(String(error));
+    } finally {
+      setIsGeneratingCover(false);
+    }
+  }, [metadata, handleChange]);
+
   return (
     <div className="space-y-4">
       <h2 className="text-sm font-semibold text-muted uppercase tracking-wider mb-4">
@@ -217,14 +236,28 @@ export function MetadataForm({ metadata, onChange }: MetadataFormProps) {
         >
           커버 이미지 경로
         </label>
-        <input
-          id="cover"
-          type="text"
-          value={metadata.cover || ""}
-          onChange={(e) => handleChange("cover", e.target.value || undefined)}
-          placeholder="/images/posts/cover.webp"
-          className="w-full px-3 py-2 bg-border/30 border border-border rounded-lg text-foreground placeholder:text-muted focus:outline-none focus:border-accent/50 transition-colors"
-        />
+        <div className="flex gap-2">
+          <input
+            id="cover"
+            type="text"
+            value={metadata.cover || ""}
+            onChange={(e) => handleChange("cover", e.target.value || undefined)}
+            placeholder="/images/posts/cover.webp"
+            className="w-full px-3 py-2 bg-border/30 border border-border rounded-lg text-foreground placeholder:text-muted focus:outline-none focus:border-accent/50 transition-colors"
+          />
+          <button
+            type="button"
+            onClick={handleGenerateCover}
+            disabled={isGeneratingCover || !metadata.title}
+            className="shrink-0 px-3 py-2 text-sm border border-border rounded-lg text-foreground hover:border-accent/50 disabled:opacity-50 transition-colors"
+          >
+            {isGeneratingCover ? "생성 중..." : "생성"}
+          </button>
+        </div>
+        <p className="text-xs text-muted">
+          비워 두면 제출할 때 제목과 시리즈 색상으로 커버를 만듭니다.
+        </p>
+        {coverError && <p className="text-xs text-red-500">{coverError}</p>}
       </div>
 
       {/* Draft Toggle */}
@@ -1,5 +1,7 @@
 "use client";
 
+import type { PublishMode } from "@/lib/tauri";
+
 type ViewMode = "split" | "editor" | "preview";
 
 interface ToolbarProps {
@@ -7,6 +9,10 @@ interface ToolbarProps {
   onViewModeChange: (mode: ViewMode) => void;
   onSave: () => void;
   onSubmit: () => void;
+  publishMode: PublishMode;
+  onPublishModeChange: (mode: PublishMode) => void;
+  normalizeTags: boolean;
+  onNormalizeTagsChange: (normalize: boolean) => void;
   isSubmitting: boolean;
   isValid: boolean;
 }
@@ -16,6 +22,10 @@ export function Toolbar({
   onViewModeChange,
   onSave,
   onSubmit,
+  publishMode,
+  onPublishModeChange,
+  normalizeTags,
+  onNormalizeTagsChange,
   isSubmitting,
   isValid,
 }: ToolbarProps) {
@@ -58,6 +68,30 @@ export function Toolbar({
         </button>
       </div>
 
+      {/* Submit Options */}
+      <div className="flex items-center gap-3 text-sm text-muted">
+        <label
+          className="flex items-center gap-1.5"
+          title="태그 대소문자와 표기를 기존 태그에 맞춥니다"
+        >
+          <input
+            type="checkbox"
+            checked={normalizeTags}
+            onChange={(e) => onNormalizeTagsChange(e.target.checked)}
+          />
+          태그 정리
+        </label>
+        <select
+          value={publishMode}
+          onChange={(e) => onPublishModeChange(e.target.value as PublishMode)}
+          className="px-2 py-1.5 bg-border/30 border border-border rounded-lg text-foreground focus:outline-none focus:border-accent/50"
+          title="제출 방식"
+        >
+          <option value="push">바로 푸시</option>
+          <option value="pullRequest">Pull Request</option>
+        </select>
+      </div>
+
       {/* Actions */}
       <div className="flex items-center gap-2">
         <button
@@ -53,10 +53,18 @@ const components = {
       {...props}
     />
   ),
-  img: (props: React.ImgHTMLAttributes<HTMLImageElement>) => (
+  img: ({
+    // next/image props the blog uses for the blur placeholder; not valid on <img>
+    blurDataURL: _blurDataURL,
+    placeholder: _placeholder,
+    ...props
+  }: React.ImgHTMLAttributes<HTMLImageElement> & {
+    blurDataURL?: string;
+    placeholder?: string;
+  }) => (
     // eslint-disable-next-line @next/next/no-img-element
     <img
-      className="my-4 rounded-lg border border-border max-w-full"
+      className="my-4 rounded-lg border border-border max-w-full h-auto"
       alt={props.alt || ""}
       {...props}
     />
@@ -13,9 +13,63 @@ export async function isTauri(): Promise<boolean> {
   }
 }
 
+export interface ImageVariant {
+  path: string;
+  width: number;
+  height: number;
+}
+
+/** Metadata an upload had that the processed image doesn't keep */
+export interface StrippedMetadata {
+  orientation: number | null;
+  hasLocation: boolean;
+  exifFields: string[];
+  xmp: boolean;
+  iccProfile: boolean;
+}
+
+export type MediaKind = "image" | "animated" | "video";
+
 export interface ProcessImageResult {
+  kind: MediaKind;
   path: string;
   localPath: string;
+  width: number;
+  height: number;
+  variants: ImageVariant[];
+  srcSet: string;
+  blurDataUrl: string;
+  poster: string | null;
+  stripped: StrippedMetadata;
+}
+
+/**
+ * MDX for a processed image: intrinsic size to avoid layout shift, `srcSet` for
+ * smaller screens and a blur placeholder while it loads. Videos autoplay muted in a
+ * loop like the GIFs they usually replace.
+ */
+export function imageToMdx(result: ProcessImageResult, alt: string): string {
+  const escapedAlt = alt.replace(/"/g, "&quot;");
+  const size = `width={${result.width}} height={${result.height}}`;
+
+  switch (result.kind) {
+    case "video":
+      return (
+        `<video src="${result.path}" poster="${result.poster}" ${size} ` +
+        `aria-label="${escapedAlt}" autoPlay loop muted playsInline />`
+      );
+    case "animated":
+      return (
+        `<img src="${result.path}" alt="${escapedAlt}" ${size} ` +
+        `placeholder="blur" blurDataURL="${result.blurDataUrl}" />`
+      );
+    case "image":
+      return (
+        `<img src="${result.path}" alt="${escapedAlt}" ${size} ` +
+        `srcSet="${result.srcSet}" sizes="(max-width: ${result.width}px) 100vw, ${result.width}px" ` +
+        `placeholder="blur" blurDataURL="${result.blurDataUrl}" />`
+      );
+  }
 }
 
 export async function processImage(
@@ -26,7 +80,16 @@ export async function processImage(
   return invoke<ProcessImageResult>("process_image", { fileName, data });
 }
 
+export async function inspectImageMetadata(
+  data: number[],
+): Promise<StrippedMetadata> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<StrippedMetadata>("inspect_image_metadata", { data });
+}
+
 export interface SubmitPostMetadata {
+  /** Filename of the post; generated from the title when empty */
+  slug?: string;
   title: string;
   description: string;
   createdAt: string;
@@ -38,19 +101,242 @@ export interface SubmitPostMetadata {
   draft: boolean;
 }
 
+/**
+ * Publish a post. Pass the slug the post was loaded with as `existingSlug` to update it
+ * instead of adding a new post.
+ */
 export async function submitPost(
   metadata: SubmitPostMetadata,
   content: string,
   localImages: Record<string, string>,
-): Promise<void> {
+  existingSlug?: string,
+  publishMode?: PublishMode,
+  normalizeTags?: boolean,
+): Promise<SubmitPostResult> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<SubmitPostResult>("submit_post", {
+    metadata,
+    content,
+    localImages,
+    existingSlug,
+    publishMode,
+    normalizeTags,
+  });
+}
+
+/** A problem found in post content; errors block submitting */
+export interface Diagnostic {
+  severity: "error" | "warning";
+  rule:
+    | "syntax"
+    | "unknownComponent"
+    | "esm"
+    | "emptyHeading"
+    | "missingAlt"
+    | "codeLanguage"
+    | "brokenLink";
+  message: string;
+  /** 1-based, in the content without frontmatter */
+  line: number;
+  column: number;
+}
+
+export async function validatePost(content: string): Promise<Diagnostic[]> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<Diagnostic[]>("validate_post", { content });
+}
+
+/** A `/<slug>` post link or image link that doesn't resolve */
+export interface BrokenLink {
+  url: string;
+  /** `wrongRoute`: `/posts/<slug>`, while posts are served at `/<slug>` */
+  problem: "missingPost" | "draftPost" | "wrongRoute" | "missingFile";
+  line: number;
+  column: number;
+  /** Existing posts or images with similar names, closest first */
+  suggestions: string[];
+}
+
+export async function checkLinks(
+  content: string,
+  slug?: string,
+  localImages?: Record<string, string>,
+): Promise<BrokenLink[]> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<BrokenLink[]>("check_links", { content, slug, localImages });
+}
+
+export interface SubmitPreview {
+  slug: string;
+  /** MDX file relative to the blog root */
+  path: string;
+  /** Whether an existing file would be overwritten */
+  overwrite: boolean;
+  mdx: string;
+  /** Unified diff against the existing file */
+  diff: string | null;
+  /** Public paths of images the submit would add */
+  images: string[];
+  /** Files the commit would include, relative to the blog root */
+  files: string[];
+  commitMessage: string;
+  warnings: string[];
+  diagnostics: Diagnostic[];
+}
+
+export async function previewSubmit(
+  metadata: SubmitPostMetadata,
+  content: string,
+  localImages: Record<string, string>,
+  existingSlug?: string,
+): Promise<SubmitPreview> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<SubmitPreview>("preview_submit", {
+    metadata,
+    content,
+    localImages,
+    existingSlug,
+  });
+}
+
+export type PublishMode = "push" | "pullRequest";
+
+export interface SubmitPostResult {
+  slug: string;
+  warnings: string[];
+  /** Set in pull-request mode */
+  pullRequestUrl: string | null;
+}
+
+/** Take a post off the blog by setting `draft: true` and committing it */
+export async function unpublishPost(
+  slug: string,
+  publishMode?: PublishMode,
+): Promise<SubmitPostResult> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<SubmitPostResult>("unpublish_post", { slug, publishMode });
+}
+
+/**
+ * Delete a post and commit the deletion. With `deleteImages`, images no other post
+ * or draft uses are deleted too.
+ */
+export async function deletePost(
+  slug: string,
+  deleteImages?: boolean,
+  publishMode?: PublishMode,
+): Promise<SubmitPostResult> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<SubmitPostResult>("delete_post", {
+    slug,
+    deleteImages,
+    publishMode,
+  });
+}
+
+/** A commit that changed a post */
+export interface PostRevision {
+  commit: string;
+  /** Author date, RFC 3339 */
+  date: string;
+  author: string;
+  message: string;
+  additions: number;
+  deletions: number;
+  /** Whether the commit deleted the post */
+  deleted: boolean;
+}
+
+export interface PostRevisionContent {
+  slug: string;
+  metadata: SubmitPostMetadata;
+  content: string;
+}
+
+export async function listPostRevisions(
+  slug: string,
+): Promise<PostRevision[]> {
   const { invoke } = await import("@tauri-apps/api/core");
-  return invoke("submit_post", {
+  return invoke<PostRevision[]>("list_post_revisions", { slug });
+}
+
+export async function loadPostRevision(
+  slug: string,
+  commit: string,
+): Promise<PostRevisionContent> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<PostRevisionContent>("load_post_revision", { slug, commit });
+}
+
+/** Restore a post as it was at `commit`, committed as a new revision */
+export async function restorePostRevision(
+  slug: string,
+  commit: string,
+  publishMode?: PublishMode,
+): Promise<SubmitPostResult> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<SubmitPostResult>("restore_post_revision", {
+    slug,
+    commit,
+    publishMode,
+  });
+}
+
+export type QueueStatus = "pending" | "publishing" | "published" | "failed";
+
+/** A post waiting in the publish queue */
+export interface QueuedPost {
+  id: string;
+  /** RFC 3339 */
+  publishAt: string;
+  metadata: SubmitPostMetadata;
+  content: string;
+  existingSlug: string | null;
+  publishMode: PublishMode;
+  normalizeTags: boolean;
+  status: QueueStatus;
+  /** Failed submits so far */
+  attempts: number;
+  /** Error of the last failed submit */
+  error: string | null;
+  /** When a failed submit is retried */
+  retryAt: string | null;
+  slug: string | null;
+  pullRequestUrl: string | null;
+  publishedAt: string | null;
+}
+
+/** Queue a post to be submitted at `publishAt`, an RFC 3339 time in the future */
+export async function queuePost(
+  metadata: SubmitPostMetadata,
+  content: string,
+  localImages: Record<string, string>,
+  publishAt: string,
+  existingSlug?: string,
+  publishMode?: PublishMode,
+): Promise<QueuedPost> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<QueuedPost>("queue_post", {
     metadata,
     content,
     localImages,
+    publishAt,
+    existingSlug,
+    publishMode,
   });
 }
 
+export async function listQueuedPosts(): Promise<QueuedPost[]> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<QueuedPost[]>("list_queued_posts");
+}
+
+/** Take a post off the queue, or clear a published or failed one */
+export async function cancelQueuedPost(id: string): Promise<void> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke("cancel_queued_post", { id });
+}
+
 export async function saveDraft(
   metadata: SubmitPostMetadata,
   content: string,
@@ -59,6 +345,14 @@ export async function saveDraft(
   return invoke("save_draft", { metadata, content });
 }
 
+export async function generateCover(
+  metadata: SubmitPostMetadata,
+  existingSlug?: string,
+): Promise<string> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<string>("generate_cover", { metadata, existingSlug });
+}
+
 export async function selectImageFile(): Promise<string | null> {
   const { open } = await import("@tauri-apps/plugin-dialog");
 